@@ -0,0 +1,195 @@
+use std::fmt;
+
+use rdev::Key;
+
+use super::keys::{key_to_string, parse_key};
+use super::state::ModifierState;
+
+/// 组合键中不区分左右的修饰键写法, 模拟时使用左侧按键, 第一个名称用于写入配置文件
+const MODIFIER_ALIASES: &[(&str, Key)] = &[
+    ("ctrl", Key::ControlLeft),
+    ("control", Key::ControlLeft),
+    ("shift", Key::ShiftLeft),
+    ("alt", Key::Alt),
+    ("win", Key::MetaLeft),
+    ("meta", Key::MetaLeft),
+];
+
+/// 要模拟发出的组合键, 例如 `ctrl+shift+tab` 或 `pageup`
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyCombo {
+    pub modifiers: Vec<Key>,
+    pub key: Key,
+}
+
+impl KeyCombo {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let mut parts = value
+            .split('+')
+            .map(|part| {
+                let part = part.trim();
+                parse_combo_key(part).ok_or_else(|| format!("unknown key '{part}' in '{value}'"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let key = parts
+            .pop()
+            .ok_or_else(|| format!("empty key combo '{value}'"))?;
+
+        let mut modifiers = Vec::with_capacity(parts.len());
+        for modifier in parts {
+            if !ModifierState::is_modifier(modifier) {
+                return Err(format!(
+                    "'{}' in '{value}' is not a modifier, only the last key can be a normal key",
+                    key_to_string(modifier)
+                ));
+            }
+            if modifiers.contains(&modifier) {
+                return Err(format!(
+                    "duplicate modifier '{}' in '{value}'",
+                    key_to_string(modifier)
+                ));
+            }
+            modifiers.push(modifier);
+        }
+
+        Ok(Self { modifiers, key })
+    }
+
+    /// 生成模拟事件序列 (key, 是否按下): 先抬起组合键不需要的已按下修饰键,
+    /// 按下组合键, 再恢复修饰键原来的状态
+    pub fn plan(&self, held: ModifierState) -> Vec<(Key, bool)> {
+        let held = held.pressed();
+        let lifted: Vec<Key> = held
+            .iter()
+            .filter(|key| !self.modifiers.contains(key))
+            .copied()
+            .collect();
+        let pressed: Vec<Key> = self
+            .modifiers
+            .iter()
+            .filter(|key| !held.contains(key))
+            .copied()
+            .collect();
+
+        let mut events = Vec::with_capacity(2 * (lifted.len() + pressed.len()) + 2);
+        events.extend(lifted.iter().map(|&key| (key, false)));
+        events.extend(pressed.iter().map(|&key| (key, true)));
+        events.extend([(self.key, true), (self.key, false)]);
+        events.extend(pressed.iter().rev().map(|&key| (key, false)));
+        events.extend(lifted.iter().map(|&key| (key, true)));
+        events
+    }
+}
+
+impl fmt::Display for KeyCombo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for &modifier in &self.modifiers {
+            write!(f, "{}+", combo_key_to_string(modifier))?;
+        }
+        write!(f, "{}", combo_key_to_string(self.key))
+    }
+}
+
+fn parse_combo_key(value: &str) -> Option<Key> {
+    let normalized = value.to_ascii_lowercase();
+    MODIFIER_ALIASES
+        .iter()
+        .find(|(name, _)| *name == normalized)
+        .map(|(_, key)| *key)
+        .or_else(|| parse_key(value))
+}
+
+fn combo_key_to_string(key: Key) -> String {
+    MODIFIER_ALIASES
+        .iter()
+        .find(|(_, alias)| *alias == key)
+        .map(|(name, _)| name.to_string())
+        .unwrap_or_else(|| key_to_string(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use rdev::Key;
+
+    use super::KeyCombo;
+    use crate::app::state::ModifierState;
+
+    #[test]
+    fn parses_and_formats_combos() {
+        let combo = KeyCombo::parse("Ctrl + Shift+tab").unwrap();
+        assert_eq!(combo.modifiers, [Key::ControlLeft, Key::ShiftLeft]);
+        assert_eq!(combo.key, Key::Tab);
+        assert_eq!(combo.to_string(), "ctrl+shift+tab");
+
+        let combo = KeyCombo::parse("pageup").unwrap();
+        assert!(combo.modifiers.is_empty());
+        assert_eq!(combo.to_string(), "pageup");
+
+        let combo = KeyCombo::parse("rctrl+alt+vk:0xa6").unwrap();
+        assert_eq!(combo.modifiers, [Key::ControlRight, Key::Alt]);
+        assert_eq!(KeyCombo::parse(&combo.to_string()), Ok(combo));
+
+        assert!(KeyCombo::parse("").is_err());
+        assert!(KeyCombo::parse("ctrl+").is_err());
+        assert!(KeyCombo::parse("ctrl+hyper").is_err());
+        assert!(
+            KeyCombo::parse("a+b")
+                .unwrap_err()
+                .contains("not a modifier")
+        );
+        assert!(
+            KeyCombo::parse("ctrl+control+c")
+                .unwrap_err()
+                .contains("duplicate")
+        );
+    }
+
+    #[test]
+    fn plan_without_held_modifiers_presses_combo() {
+        let combo = KeyCombo::parse("ctrl+right").unwrap();
+        assert_eq!(
+            combo.plan(ModifierState::default()),
+            [
+                (Key::ControlLeft, true),
+                (Key::RightArrow, true),
+                (Key::RightArrow, false),
+                (Key::ControlLeft, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn plan_lifts_and_restores_held_modifiers() {
+        let mut held = ModifierState::default();
+        held.update(Key::ShiftLeft, true);
+        held.update(Key::ControlLeft, true);
+        held.update(Key::Alt, true);
+
+        let combo = KeyCombo::parse("ctrl+shift+tab").unwrap();
+        assert_eq!(
+            combo.plan(held),
+            [
+                (Key::Alt, false),
+                (Key::Tab, true),
+                (Key::Tab, false),
+                (Key::Alt, true),
+            ]
+        );
+
+        let combo = KeyCombo::parse("pagedown").unwrap();
+        assert_eq!(
+            combo.plan(held),
+            [
+                (Key::ShiftLeft, false),
+                (Key::ControlLeft, false),
+                (Key::Alt, false),
+                (Key::PageDown, true),
+                (Key::PageDown, false),
+                (Key::ShiftLeft, true),
+                (Key::ControlLeft, true),
+                (Key::Alt, true),
+            ]
+        );
+    }
+}
@@ -1,20 +1,27 @@
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use rdev::Key;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
-use super::state::{Action, Direction};
+use super::combo::KeyCombo;
+use super::keys::{key_to_string, parse_key};
+use super::layout::{MonitorTarget, Snap};
+use super::snippet::Snippet;
+use super::state::{Action, Direction, ExecSpec, ModifierState, WindowCommand, WindowMode};
+use super::taphold::TapHoldTiming;
 
-/// 单个热键规则，绑定 CapsLock + key 组合到特定操作
+/// 单个热键规则，绑定 触发键 + key 组合到特定操作
 ///
 /// 例如: CapsLock + H 切换窗口焦点到左边的窗口
 #[derive(Debug, Clone)]
 pub struct HotkeyRule {
     /// 触发此规则的按键 (h, l, k, j, e, v, p, left, right, up, down, space, enter, tab, f1-f12 等)
     pub key: Key,
-    /// Shift 键的状态要求 (任意/必须按下/必须未按下)
-    pub shift: ShiftRequirement,
+    /// 各修饰键的状态要求 (任意/必须按下/必须未按下)
+    pub modifiers: ModifierMask,
     /// 触发此规则时执行的操作 (切换窗口、打开程序、切换输入法等)
     pub action: Action,
     /// 是否拦截键盘事件，阻止系统处理此按键 (true: 吞键, false: 传递给系统)
@@ -27,62 +34,243 @@ pub struct HotkeyRule {
     pub description: String,
 }
 
-/// CapsLock 快捷键中 Shift 键的状态要求
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum ShiftRequirement {
-    /// 任意: 无论 Shift 键是否按下都能触发此规则 (CapsLock + key)
+/// CapsLock 快捷键中单个修饰键的状态要求
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ModifierRequirement {
+    /// 任意: 无论该修饰键是否按下都能触发此规则
+    #[default]
     Any,
-    /// 按下: 仅当 Shift 键被按下时触发此规则 (CapsLock + Shift + key)
+    /// 按下: 仅当该修饰键被按下时触发此规则 (例如 CapsLock + Ctrl + key)
     Down,
-    /// 未按下: 仅当 Shift 键未被按下时触发此规则 (CapsLock + key，不含 Shift)
+    /// 未按下: 仅当该修饰键未被按下时触发此规则
     Up,
 }
 
-/// CapsLock 热键配置的完整集合，包括单击动作和所有组合键规则
+impl ModifierRequirement {
+    fn matches(self, pressing: bool) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Down => pressing,
+            Self::Up => !pressing,
+        }
+    }
+}
+
+/// CapsLock 快捷键对各修饰键的要求, Ctrl/Alt/Win 不区分左右
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ModifierMask {
+    pub lshift: ModifierRequirement,
+    pub rshift: ModifierRequirement,
+    pub ctrl: ModifierRequirement,
+    pub alt: ModifierRequirement,
+    pub win: ModifierRequirement,
+}
+
+impl ModifierMask {
+    fn requirements(&self) -> [(&'static str, ModifierRequirement); 5] {
+        [
+            ("lshift", self.lshift),
+            ("rshift", self.rshift),
+            ("ctrl", self.ctrl),
+            ("alt", self.alt),
+            ("win", self.win),
+        ]
+    }
+
+    pub fn matches(&self, state: ModifierState) -> bool {
+        self.lshift.matches(state.lshift)
+            && self.rshift.matches(state.rshift)
+            && self.ctrl.matches(state.ctrl())
+            && self.alt.matches(state.alt())
+            && self.win.matches(state.win())
+    }
+
+    /// 有明确要求 (非 any) 的修饰键数量, 数量越多规则越具体
+    fn specificity(&self) -> usize {
+        self.requirements()
+            .iter()
+            .filter(|(_, requirement)| *requirement != ModifierRequirement::Any)
+            .count()
+    }
+}
+
+/// 一个快捷键层: 按住触发键时, 其它按键按照该层的规则处理
 #[derive(Debug, Clone)]
-pub struct HotkeyConfig {
-    /// CapsLock 单击（无其他键按下时）执行的动作 (通常是切换输入法)
-    pub caps_tap_action: Action,
-    /// 所有 CapsLock + key 组合键规则的列表
+pub struct Layer {
+    /// 层名称, 对应配置中的 `[layers.<name>]`
+    pub name: String,
+    /// 按住后激活此层的按键
+    pub trigger: Key,
+    /// 单击触发键（无其他键按下时）执行的动作 (CapsLock 层通常是切换输入法)
+    pub tap_action: Action,
+    /// 单击/按住判定的时间参数
+    pub timing: TapHoldTiming,
+    /// 所有 触发键 + key 组合键规则的列表
     pub rules: Vec<HotkeyRule>,
 }
 
-impl HotkeyConfig {
-    pub fn resolve(&self, key: Key, lshift_pressing: bool) -> Option<&HotkeyRule> {
-        self.rules.iter().find(|rule| {
-            if rule.key != key {
-                return false;
-            }
+impl Layer {
+    /// CapsLock 以外的触发键是普通字符键, 单击时仍需输出原本的字符
+    pub fn types_on_tap(&self) -> bool {
+        self.trigger != Key::CapsLock
+    }
 
-            match rule.shift {
-                ShiftRequirement::Any => true,
-                ShiftRequirement::Down => lshift_pressing,
-                ShiftRequirement::Up => !lshift_pressing,
+    /// 查找匹配的规则, 多条规则同时匹配时选择修饰键要求最具体的一条, 相同时取靠前的
+    pub fn resolve(&self, key: Key, modifiers: ModifierState) -> Option<&HotkeyRule> {
+        let mut selected: Option<&HotkeyRule> = None;
+        for rule in &self.rules {
+            if rule.key != key || !rule.modifiers.matches(modifiers) {
+                continue;
             }
-        })
+            if selected
+                .is_none_or(|best| rule.modifiers.specificity() > best.modifiers.specificity())
+            {
+                selected = Some(rule);
+            }
+        }
+        selected
+    }
+}
+
+/// 热键配置的完整集合，包括所有层
+#[derive(Debug, Clone)]
+pub struct HotkeyConfig {
+    pub layers: Vec<Layer>,
+}
+
+impl HotkeyConfig {
+    /// 查找以 key 为触发键的层
+    pub fn layer_index(&self, key: Key) -> Option<usize> {
+        self.layers.iter().position(|layer| layer.trigger == key)
+    }
+
+    pub fn rule_count(&self) -> usize {
+        self.layers.iter().map(|layer| layer.rules.len()).sum()
     }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 struct FileConfig {
+    /// 旧版写法, 等同于 trigger = "capslock" 的 `[layers.caps]`
     caps: Option<CapsConfig>,
+    layers: Option<BTreeMap<String, LayerConfig>>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 struct CapsConfig {
-    tap_action: Option<String>,
+    tap_action: Option<ActionConfig>,
+    tap_timeout_ms: Option<u64>,
+    hold_delay_ms: Option<u64>,
+    permissive_hold: Option<bool>,
+    bindings: Option<Vec<BindingConfig>>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct LayerConfig {
+    trigger: String,
+    tap_action: Option<ActionConfig>,
+    tap_timeout_ms: Option<u64>,
+    hold_delay_ms: Option<u64>,
+    permissive_hold: Option<bool>,
     bindings: Option<Vec<BindingConfig>>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 struct BindingConfig {
     key: String,
-    action: String,
+    action: ActionConfig,
+    /// 旧版写法, 等同于 `lshift`
     shift: Option<String>,
+    lshift: Option<String>,
+    rshift: Option<String>,
+    ctrl: Option<String>,
+    alt: Option<String>,
+    win: Option<String>,
     suppress: Option<bool>,
     pending: Option<bool>,
 }
 
+/// action 配置, 既可以是结构化的表, 也可以是旧版的 `name:params` 字符串
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum ActionConfig {
+    Legacy(String),
+    Table(ActionTable),
+}
+
+impl<'de> Deserialize<'de> for ActionConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match toml::Value::deserialize(deserializer)? {
+            toml::Value::String(value) => Ok(Self::Legacy(value)),
+            value => value
+                .try_into()
+                .map(Self::Table)
+                .map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+/// 结构化的 action, 例如 `{ type = "switch_or_open", title = "Code", program = "Code.exe" }`
+///
+/// 参数原样保留, 不会被转换成小写, 程序路径中的冒号也不会被当作分隔符
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum ActionTable {
+    None,
+    SwitchIme,
+    SwitchLeft,
+    SwitchRight,
+    SwitchUp,
+    SwitchDown,
+    ScrollUp,
+    ScrollDown,
+    Maximize,
+    Minimize,
+    Restore,
+    Close,
+    ToggleTopmost,
+    Snap {
+        position: String,
+    },
+    MoveToMonitor {
+        monitor: String,
+    },
+    OpenProgram {
+        program: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        args: Vec<String>,
+    },
+    SwitchWindow {
+        title: String,
+    },
+    SwitchOrOpen {
+        title: String,
+        program: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        args: Vec<String>,
+    },
+    SendKeys {
+        keys: String,
+    },
+    TypeText {
+        text: String,
+    },
+    Exec {
+        program: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        args: Vec<String>,
+        cwd: Option<String>,
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        env: BTreeMap<String, String>,
+        window: Option<String>,
+        wait: Option<bool>,
+    },
+    Sequence {
+        steps: Vec<ActionConfig>,
+        delay_ms: Option<u64>,
+    },
+}
+
 pub fn load_hotkey_config() -> HotkeyConfig {
     let path = config_path();
     let Some(path) = path else {
@@ -107,8 +295,8 @@ pub fn load_hotkey_config() -> HotkeyConfig {
             tracing::info!(
                 target: "fncaps::config",
                 path = %path.display(),
-                rules = cfg.rules.len(),
-                ?cfg.caps_tap_action,
+                layers = cfg.layers.len(),
+                rules = cfg.rule_count(),
                 "hotkey config loaded"
             );
             cfg
@@ -130,6 +318,12 @@ fn load_hotkey_config_from_file(path: &Path) -> Result<HotkeyConfig, String> {
     build_config_from_file(parsed)
 }
 
+/// 从 TOML 文本构建配置, 供按键处理的测试使用
+#[cfg(test)]
+pub fn parse_hotkey_config(raw: &str) -> Result<HotkeyConfig, String> {
+    build_config_from_file(toml::from_str(raw).map_err(|e| e.to_string())?)
+}
+
 /// 将默认配置转换为 FileConfig 并保存到文件
 fn save_default_config(path: &Path, cfg: &HotkeyConfig) -> Result<(), String> {
     // 确保目录存在
@@ -158,167 +352,229 @@ fn save_default_config(path: &Path, cfg: &HotkeyConfig) -> Result<(), String> {
 
 /// 将 HotkeyConfig 转换为 FileConfig（用于序列化）
 fn config_to_file(cfg: &HotkeyConfig) -> FileConfig {
-    let tap_action = action_to_string(&cfg.caps_tap_action);
+    let layers = cfg
+        .layers
+        .iter()
+        .map(|layer| (layer.name.clone(), layer_to_file(layer)))
+        .collect();
 
-    let bindings = cfg
+    FileConfig {
+        caps: None,
+        layers: Some(layers),
+    }
+}
+
+fn layer_to_file(layer: &Layer) -> LayerConfig {
+    let tap_action = ActionConfig::Table(action_to_table(&layer.tap_action));
+
+    let bindings = layer
         .rules
         .iter()
         .map(|rule| {
             let key_str = key_to_string(rule.key);
-            let action_str = action_to_string(&rule.action);
-            let shift_str = match rule.shift {
-                super::config::ShiftRequirement::Any => None,
-                super::config::ShiftRequirement::Down => Some("down".to_string()),
-                super::config::ShiftRequirement::Up => Some("up".to_string()),
-            };
+            let action = ActionConfig::Table(action_to_table(&rule.action));
+            let modifiers = rule.modifiers;
 
             BindingConfig {
                 key: key_str,
-                action: action_str,
-                shift: shift_str,
+                action,
+                shift: None,
+                lshift: modifier_to_string(modifiers.lshift),
+                rshift: modifier_to_string(modifiers.rshift),
+                ctrl: modifier_to_string(modifiers.ctrl),
+                alt: modifier_to_string(modifiers.alt),
+                win: modifier_to_string(modifiers.win),
                 suppress: Some(rule.suppress),
                 pending: Some(rule.pending),
             }
         })
         .collect();
 
-    FileConfig {
-        caps: Some(CapsConfig {
-            tap_action: Some(tap_action),
-            bindings: Some(bindings),
-        }),
+    let timing = layer.timing;
+
+    LayerConfig {
+        trigger: key_to_string(layer.trigger),
+        tap_action: Some(tap_action),
+        tap_timeout_ms: timing.tap_timeout.map(|timeout| timeout.as_millis() as u64),
+        hold_delay_ms: (!timing.hold_delay.is_zero())
+            .then_some(timing.hold_delay.as_millis() as u64),
+        permissive_hold: timing.permissive_hold.then_some(true),
+        bindings: Some(bindings),
     }
 }
 
-/// 将 Action 转换为字符串表示
-fn action_to_string(action: &Action) -> String {
+/// 将修饰键要求转换为字符串表示, `any` 省略不写
+fn modifier_to_string(requirement: ModifierRequirement) -> Option<String> {
+    match requirement {
+        ModifierRequirement::Any => None,
+        ModifierRequirement::Down => Some("down".to_string()),
+        ModifierRequirement::Up => Some("up".to_string()),
+    }
+}
+
+/// 将 Action 转换为结构化的配置表示
+fn action_to_table(action: &Action) -> ActionTable {
     match action {
-        Action::None => "none".to_string(),
-        Action::SwitchTo(Direction::Left) => "switch_left".to_string(),
-        Action::SwitchTo(Direction::Right) => "switch_right".to_string(),
-        Action::SwitchTo(Direction::Up) => "switch_up".to_string(),
-        Action::SwitchTo(Direction::Down) => "switch_down".to_string(),
-        Action::Scroll(n) if *n > 0 => "scroll_up".to_string(),
-        Action::Scroll(_) => "scroll_down".to_string(),
-        Action::SwitchIme => "switch_ime".to_string(),
-        Action::OpenProgram { program } => format!("open_program:{}", program),
-        Action::SwitchWindow { title } => format!("switch_window:{}", title),
+        Action::None => ActionTable::None,
+        Action::SwitchTo(Direction::Left) => ActionTable::SwitchLeft,
+        Action::SwitchTo(Direction::Right) => ActionTable::SwitchRight,
+        Action::SwitchTo(Direction::Up) => ActionTable::SwitchUp,
+        Action::SwitchTo(Direction::Down) => ActionTable::SwitchDown,
+        Action::Scroll(n) if *n > 0 => ActionTable::ScrollUp,
+        Action::Scroll(_) => ActionTable::ScrollDown,
+        Action::SwitchIme => ActionTable::SwitchIme,
+        Action::Window(WindowCommand::Maximize) => ActionTable::Maximize,
+        Action::Window(WindowCommand::Minimize) => ActionTable::Minimize,
+        Action::Window(WindowCommand::Restore) => ActionTable::Restore,
+        Action::Window(WindowCommand::Close) => ActionTable::Close,
+        Action::Window(WindowCommand::ToggleTopmost) => ActionTable::ToggleTopmost,
+        Action::Window(WindowCommand::Snap(snap)) => ActionTable::Snap {
+            position: snap.to_string(),
+        },
+        Action::Window(WindowCommand::MoveToMonitor(target)) => ActionTable::MoveToMonitor {
+            monitor: target.to_string(),
+        },
+        Action::OpenProgram { program, args } => ActionTable::OpenProgram {
+            program: program.clone(),
+            args: args.clone(),
+        },
+        Action::SwitchWindow { title } => ActionTable::SwitchWindow {
+            title: title.clone(),
+        },
         Action::SwitchOrOpen {
             window_title,
             program,
-        } => {
-            format!("switch_or_open:{}:{}", window_title, program)
-        }
+            args,
+        } => ActionTable::SwitchOrOpen {
+            title: window_title.clone(),
+            program: program.clone(),
+            args: args.clone(),
+        },
+        Action::SendKeys(combo) => ActionTable::SendKeys {
+            keys: combo.to_string(),
+        },
+        Action::TypeText(snippet) => ActionTable::TypeText {
+            text: snippet.to_string(),
+        },
+        Action::Exec(spec) => ActionTable::Exec {
+            program: spec.program.clone(),
+            args: spec.args.clone(),
+            cwd: spec.cwd.clone(),
+            env: spec.env.clone(),
+            window: window_mode_to_string(spec.window),
+            wait: spec.wait.then_some(true),
+        },
+        Action::Sequence { steps, delay } => ActionTable::Sequence {
+            steps: steps
+                .iter()
+                .map(|step| ActionConfig::Table(action_to_table(step)))
+                .collect(),
+            delay_ms: (!delay.is_zero()).then_some(delay.as_millis() as u64),
+        },
     }
 }
 
-/// 将 Key 转换为字符串表示
-fn key_to_string(key: Key) -> String {
-    match key {
-        Key::KeyA => "a",
-        Key::KeyB => "b",
-        Key::KeyC => "c",
-        Key::KeyD => "d",
-        Key::KeyE => "e",
-        Key::KeyF => "f",
-        Key::KeyG => "g",
-        Key::KeyH => "h",
-        Key::KeyI => "i",
-        Key::KeyJ => "j",
-        Key::KeyK => "k",
-        Key::KeyL => "l",
-        Key::KeyM => "m",
-        Key::KeyN => "n",
-        Key::KeyO => "o",
-        Key::KeyP => "p",
-        Key::KeyQ => "q",
-        Key::KeyR => "r",
-        Key::KeyS => "s",
-        Key::KeyT => "t",
-        Key::KeyU => "u",
-        Key::KeyV => "v",
-        Key::KeyW => "w",
-        Key::KeyX => "x",
-        Key::KeyY => "y",
-        Key::KeyZ => "z",
-        Key::Num0 => "0",
-        Key::Num1 => "1",
-        Key::Num2 => "2",
-        Key::Num3 => "3",
-        Key::Num4 => "4",
-        Key::Num5 => "5",
-        Key::Num6 => "6",
-        Key::Num7 => "7",
-        Key::Num8 => "8",
-        Key::Num9 => "9",
-        Key::LeftArrow => "left",
-        Key::RightArrow => "right",
-        Key::UpArrow => "up",
-        Key::DownArrow => "down",
-        Key::Space => "space",
-        Key::Return => "enter",
-        Key::Tab => "tab",
-        Key::Escape => "esc",
-        Key::Backspace => "backspace",
-        Key::Home => "home",
-        Key::End => "end",
-        Key::PageUp => "pageup",
-        Key::PageDown => "pagedown",
-        Key::Insert => "insert",
-        Key::Delete => "delete",
-        Key::F1 => "f1",
-        Key::F2 => "f2",
-        Key::F3 => "f3",
-        Key::F4 => "f4",
-        Key::F5 => "f5",
-        Key::F6 => "f6",
-        Key::F7 => "f7",
-        Key::F8 => "f8",
-        Key::F9 => "f9",
-        Key::F10 => "f10",
-        Key::F11 => "f11",
-        Key::F12 => "f12",
-        _ => "unknown",
-    }
-    .to_string()
-}
+/// 旧版 `[caps]` 对应的层名称
+const CAPS_LAYER: &str = "caps";
 
 fn build_config_from_file(parsed: FileConfig) -> Result<HotkeyConfig, String> {
-    let caps = parsed.caps.unwrap_or(CapsConfig {
-        tap_action: None,
-        bindings: None,
-    });
+    let mut layers = Vec::new();
+
+    match parsed.caps {
+        Some(caps) => {
+            if parsed
+                .layers
+                .as_ref()
+                .is_some_and(|layers| layers.contains_key(CAPS_LAYER))
+            {
+                return Err("both [caps] and [layers.caps] are defined, keep only one".to_string());
+            }
+            let use_default_rules = caps.bindings.is_none();
+            let config = LayerConfig {
+                trigger: key_to_string(Key::CapsLock),
+                tap_action: caps.tap_action,
+                tap_timeout_ms: caps.tap_timeout_ms,
+                hold_delay_ms: caps.hold_delay_ms,
+                permissive_hold: caps.permissive_hold,
+                bindings: caps.bindings,
+            };
+            let mut layer = build_layer(CAPS_LAYER, config)?;
+            // 旧版 [caps] 未写 bindings 时沿用内置的默认规则
+            if use_default_rules {
+                layer.rules = default_caps_rules();
+            }
+            layers.push(layer);
+        }
+        None if parsed.layers.is_none() => return Ok(default_hotkey_config()),
+        None => {}
+    }
+
+    for (name, config) in parsed.layers.unwrap_or_default() {
+        let layer = build_layer(&name, config)?;
+        if let Some(other) = layers.iter().find(|other| other.trigger == layer.trigger) {
+            return Err(format!(
+                "layer '{name}' uses the same trigger as layer '{}'",
+                other.name
+            ));
+        }
+        layers.push(layer);
+    }
+
+    Ok(HotkeyConfig { layers })
+}
 
-    let tap_action = caps
+fn build_layer(name: &str, config: LayerConfig) -> Result<Layer, String> {
+    let trigger = parse_key(&config.trigger)
+        .ok_or_else(|| format!("layer '{name}' unknown trigger: '{}'", config.trigger))?;
+    if ModifierState::is_modifier(trigger) {
+        return Err(format!(
+            "layer '{name}' trigger '{}' is a modifier key",
+            config.trigger
+        ));
+    }
+
+    let default_tap_action = if trigger == Key::CapsLock {
+        Action::SwitchIme
+    } else {
+        Action::None
+    };
+    let tap_action = config
         .tap_action
-        .as_deref()
-        .map(parse_action)
-        .transpose()?
-        .unwrap_or(Action::SwitchIme);
+        .map(parse_action_config)
+        .transpose()
+        .map_err(|e| format!("layer '{name}' tap_action error: {e}"))?
+        .unwrap_or(default_tap_action);
 
-    let Some(bindings) = caps.bindings else {
-        return Ok(default_hotkey_config_with_tap(tap_action));
+    let timing = TapHoldTiming {
+        tap_timeout: config.tap_timeout_ms.map(Duration::from_millis),
+        hold_delay: Duration::from_millis(config.hold_delay_ms.unwrap_or(0)),
+        permissive_hold: config.permissive_hold.unwrap_or(false),
     };
 
+    let bindings = config.bindings.unwrap_or_default();
     let mut rules = Vec::with_capacity(bindings.len());
 
     for (index, binding) in bindings.into_iter().enumerate() {
-        let key = parse_key(&binding.key)
-            .ok_or_else(|| format!("bindings[{index}] unknown key: '{}'", binding.key))?;
+        let key = parse_key(&binding.key).ok_or_else(|| {
+            format!(
+                "layer '{name}' bindings[{index}] unknown key: '{}'",
+                binding.key
+            )
+        })?;
 
-        let action = parse_action(binding.action.as_str())
-            .map_err(|e| format!("bindings[{index}] action error: {e}"))?;
+        let modifiers = parse_modifiers(&binding)
+            .map_err(|e| format!("layer '{name}' bindings[{index}] modifier error: {e}"))?;
 
-        let shift = parse_shift(binding.shift.as_deref())
-            .map_err(|e| format!("bindings[{index}] shift error: {e}"))?;
+        let action = parse_action_config(binding.action)
+            .map_err(|e| format!("layer '{name}' bindings[{index}] action error: {e}"))?;
 
         let suppress = binding.suppress.unwrap_or(true);
         let pending = binding.pending.unwrap_or(true);
-        let description = format!("caps+{}", binding.key);
+        let description = describe_binding(name, &binding.key, &modifiers);
 
         rules.push(HotkeyRule {
             key,
-            shift,
+            modifiers,
             action,
             suppress,
             pending,
@@ -326,8 +582,11 @@ fn build_config_from_file(parsed: FileConfig) -> Result<HotkeyConfig, String> {
         });
     }
 
-    Ok(HotkeyConfig {
-        caps_tap_action: tap_action,
+    Ok(Layer {
+        name: name.to_string(),
+        trigger,
+        tap_action,
+        timing,
         rules,
     })
 }
@@ -344,148 +603,156 @@ fn config_path() -> Option<PathBuf> {
 }
 
 fn default_hotkey_config() -> HotkeyConfig {
-    default_hotkey_config_with_tap(Action::SwitchIme)
-}
-
-fn default_hotkey_config_with_tap(tap_action: Action) -> HotkeyConfig {
     HotkeyConfig {
-        caps_tap_action: tap_action,
-        rules: vec![
-            mk_rule(
-                Key::LeftArrow,
-                ShiftRequirement::Any,
-                Action::SwitchTo(Direction::Left),
-                true,
-                true,
-                "caps+left",
-            ),
-            mk_rule(
-                Key::KeyH,
-                ShiftRequirement::Any,
-                Action::SwitchTo(Direction::Left),
-                true,
-                true,
-                "caps+h",
-            ),
-            mk_rule(
-                Key::RightArrow,
-                ShiftRequirement::Any,
-                Action::SwitchTo(Direction::Right),
-                true,
-                true,
-                "caps+right",
-            ),
-            mk_rule(
-                Key::KeyL,
-                ShiftRequirement::Any,
-                Action::SwitchTo(Direction::Right),
-                true,
-                true,
-                "caps+l",
-            ),
-            mk_rule(
-                Key::UpArrow,
-                ShiftRequirement::Down,
-                Action::Scroll(1),
-                true,
-                false,
-                "caps+shift+up",
-            ),
-            mk_rule(
-                Key::KeyK,
-                ShiftRequirement::Down,
-                Action::Scroll(1),
-                true,
-                false,
-                "caps+shift+k",
-            ),
-            mk_rule(
-                Key::UpArrow,
-                ShiftRequirement::Up,
-                Action::SwitchTo(Direction::Up),
-                true,
-                true,
-                "caps+up",
-            ),
-            mk_rule(
-                Key::KeyK,
-                ShiftRequirement::Up,
-                Action::SwitchTo(Direction::Up),
-                true,
-                true,
-                "caps+k",
-            ),
-            mk_rule(
-                Key::DownArrow,
-                ShiftRequirement::Down,
-                Action::Scroll(-1),
-                true,
-                false,
-                "caps+shift+down",
-            ),
-            mk_rule(
-                Key::KeyJ,
-                ShiftRequirement::Down,
-                Action::Scroll(-1),
-                true,
-                false,
-                "caps+shift+j",
-            ),
-            mk_rule(
-                Key::DownArrow,
-                ShiftRequirement::Up,
-                Action::SwitchTo(Direction::Down),
-                true,
-                true,
-                "caps+down",
-            ),
-            mk_rule(
-                Key::KeyJ,
-                ShiftRequirement::Up,
-                Action::SwitchTo(Direction::Down),
-                true,
-                true,
-                "caps+j",
-            ),
-            mk_rule(
-                Key::KeyE,
-                ShiftRequirement::Any,
-                Action::OpenProgram {
-                    program: "notepad.exe".to_string(),
-                },
-                true,
-                true,
-                "caps+e",
-            ),
-            mk_rule(
-                Key::KeyV,
-                ShiftRequirement::Any,
-                Action::SwitchOrOpen {
-                    window_title: "Code".to_string(),
-                    program: "Code.exe".to_string(),
-                },
-                true,
-                true,
-                "caps+v",
-            ),
-            mk_rule(
-                Key::KeyP,
-                ShiftRequirement::Any,
-                Action::SwitchOrOpen {
-                    window_title: "PowerShell".to_string(),
-                    program: "pwsh.exe".to_string(),
-                },
-                true,
-                true,
-                "caps+p",
-            ),
-        ],
+        layers: vec![Layer {
+            name: CAPS_LAYER.to_string(),
+            trigger: Key::CapsLock,
+            tap_action: Action::SwitchIme,
+            timing: TapHoldTiming::default(),
+            rules: default_caps_rules(),
+        }],
     }
 }
 
+fn default_caps_rules() -> Vec<HotkeyRule> {
+    vec![
+        mk_rule(
+            Key::LeftArrow,
+            ModifierRequirement::Any,
+            Action::SwitchTo(Direction::Left),
+            true,
+            true,
+            "caps+left",
+        ),
+        mk_rule(
+            Key::KeyH,
+            ModifierRequirement::Any,
+            Action::SwitchTo(Direction::Left),
+            true,
+            true,
+            "caps+h",
+        ),
+        mk_rule(
+            Key::RightArrow,
+            ModifierRequirement::Any,
+            Action::SwitchTo(Direction::Right),
+            true,
+            true,
+            "caps+right",
+        ),
+        mk_rule(
+            Key::KeyL,
+            ModifierRequirement::Any,
+            Action::SwitchTo(Direction::Right),
+            true,
+            true,
+            "caps+l",
+        ),
+        mk_rule(
+            Key::UpArrow,
+            ModifierRequirement::Down,
+            Action::Scroll(1),
+            true,
+            false,
+            "caps+shift+up",
+        ),
+        mk_rule(
+            Key::KeyK,
+            ModifierRequirement::Down,
+            Action::Scroll(1),
+            true,
+            false,
+            "caps+shift+k",
+        ),
+        mk_rule(
+            Key::UpArrow,
+            ModifierRequirement::Up,
+            Action::SwitchTo(Direction::Up),
+            true,
+            true,
+            "caps+up",
+        ),
+        mk_rule(
+            Key::KeyK,
+            ModifierRequirement::Up,
+            Action::SwitchTo(Direction::Up),
+            true,
+            true,
+            "caps+k",
+        ),
+        mk_rule(
+            Key::DownArrow,
+            ModifierRequirement::Down,
+            Action::Scroll(-1),
+            true,
+            false,
+            "caps+shift+down",
+        ),
+        mk_rule(
+            Key::KeyJ,
+            ModifierRequirement::Down,
+            Action::Scroll(-1),
+            true,
+            false,
+            "caps+shift+j",
+        ),
+        mk_rule(
+            Key::DownArrow,
+            ModifierRequirement::Up,
+            Action::SwitchTo(Direction::Down),
+            true,
+            true,
+            "caps+down",
+        ),
+        mk_rule(
+            Key::KeyJ,
+            ModifierRequirement::Up,
+            Action::SwitchTo(Direction::Down),
+            true,
+            true,
+            "caps+j",
+        ),
+        mk_rule(
+            Key::KeyE,
+            ModifierRequirement::Any,
+            Action::OpenProgram {
+                program: "notepad.exe".to_string(),
+                args: Vec::new(),
+            },
+            true,
+            true,
+            "caps+e",
+        ),
+        mk_rule(
+            Key::KeyV,
+            ModifierRequirement::Any,
+            Action::SwitchOrOpen {
+                window_title: "Code".to_string(),
+                program: "Code.exe".to_string(),
+                args: Vec::new(),
+            },
+            true,
+            true,
+            "caps+v",
+        ),
+        mk_rule(
+            Key::KeyP,
+            ModifierRequirement::Any,
+            Action::SwitchOrOpen {
+                window_title: "PowerShell".to_string(),
+                program: "pwsh.exe".to_string(),
+                args: Vec::new(),
+            },
+            true,
+            true,
+            "caps+p",
+        ),
+    ]
+}
+
 fn mk_rule(
     key: Key,
-    shift: ShiftRequirement,
+    lshift: ModifierRequirement,
     action: Action,
     suppress: bool,
     pending: bool,
@@ -493,7 +760,10 @@ fn mk_rule(
 ) -> HotkeyRule {
     HotkeyRule {
         key,
-        shift,
+        modifiers: ModifierMask {
+            lshift,
+            ..ModifierMask::default()
+        },
         action,
         suppress,
         pending,
@@ -501,41 +771,198 @@ fn mk_rule(
     }
 }
 
-fn parse_shift(value: Option<&str>) -> Result<ShiftRequirement, String> {
+fn parse_modifiers(binding: &BindingConfig) -> Result<ModifierMask, String> {
+    if binding.shift.is_some() && binding.lshift.is_some() {
+        return Err("`shift` is an alias of `lshift`, set only one of them".to_string());
+    }
+
+    Ok(ModifierMask {
+        lshift: parse_modifier(
+            "lshift",
+            binding.lshift.as_deref().or(binding.shift.as_deref()),
+        )?,
+        rshift: parse_modifier("rshift", binding.rshift.as_deref())?,
+        ctrl: parse_modifier("ctrl", binding.ctrl.as_deref())?,
+        alt: parse_modifier("alt", binding.alt.as_deref())?,
+        win: parse_modifier("win", binding.win.as_deref())?,
+    })
+}
+
+fn parse_modifier(name: &str, value: Option<&str>) -> Result<ModifierRequirement, String> {
+    let Some(value) = value else {
+        return Ok(ModifierRequirement::Any);
+    };
+
+    match value.trim().to_ascii_lowercase().as_str() {
+        "any" => Ok(ModifierRequirement::Any),
+        "down" | "pressed" => Ok(ModifierRequirement::Down),
+        "up" | "released" => Ok(ModifierRequirement::Up),
+        other => Err(format!(
+            "unsupported {name} mode '{other}', expected any/down/up"
+        )),
+    }
+}
+
+/// 生成规则的可读描述, 只列出要求按下的修饰键, 例如 "caps+ctrl+h"
+fn describe_binding(layer: &str, key: &str, modifiers: &ModifierMask) -> String {
+    let mut description = layer.to_string();
+    for (name, requirement) in modifiers.requirements() {
+        if requirement == ModifierRequirement::Down {
+            description.push('+');
+            description.push_str(name);
+        }
+    }
+    description.push('+');
+    description.push_str(key);
+    description
+}
+
+fn parse_action_config(config: ActionConfig) -> Result<Action, String> {
+    match config {
+        ActionConfig::Legacy(value) => parse_action(&value),
+        ActionConfig::Table(table) => action_from_table(table),
+    }
+}
+
+fn action_from_table(table: ActionTable) -> Result<Action, String> {
+    let action = match table {
+        ActionTable::None => Action::None,
+        ActionTable::SwitchIme => Action::SwitchIme,
+        ActionTable::SwitchLeft => Action::SwitchTo(Direction::Left),
+        ActionTable::SwitchRight => Action::SwitchTo(Direction::Right),
+        ActionTable::SwitchUp => Action::SwitchTo(Direction::Up),
+        ActionTable::SwitchDown => Action::SwitchTo(Direction::Down),
+        ActionTable::ScrollUp => Action::Scroll(1),
+        ActionTable::ScrollDown => Action::Scroll(-1),
+        ActionTable::Maximize => Action::Window(WindowCommand::Maximize),
+        ActionTable::Minimize => Action::Window(WindowCommand::Minimize),
+        ActionTable::Restore => Action::Window(WindowCommand::Restore),
+        ActionTable::Close => Action::Window(WindowCommand::Close),
+        ActionTable::ToggleTopmost => Action::Window(WindowCommand::ToggleTopmost),
+        ActionTable::Snap { position } => {
+            Action::Window(WindowCommand::Snap(Snap::parse(&position)?))
+        }
+        ActionTable::MoveToMonitor { monitor } => Action::Window(WindowCommand::MoveToMonitor(
+            MonitorTarget::parse(&monitor)?,
+        )),
+        ActionTable::OpenProgram { program, args } => {
+            if program.trim().is_empty() {
+                return Err("open_program requires a program name".to_string());
+            }
+            Action::OpenProgram { program, args }
+        }
+        ActionTable::SwitchWindow { title } => {
+            if title.trim().is_empty() {
+                return Err("switch_window requires a window title".to_string());
+            }
+            Action::SwitchWindow { title }
+        }
+        ActionTable::SwitchOrOpen {
+            title,
+            program,
+            args,
+        } => {
+            if title.trim().is_empty() || program.trim().is_empty() {
+                return Err("switch_or_open requires non-empty title and program".to_string());
+            }
+            Action::SwitchOrOpen {
+                window_title: title,
+                program,
+                args,
+            }
+        }
+        ActionTable::SendKeys { keys } => Action::SendKeys(KeyCombo::parse(&keys)?),
+        ActionTable::TypeText { text } => {
+            if text.is_empty() {
+                return Err("type_text requires non-empty text".to_string());
+            }
+            Action::TypeText(Snippet::parse(&text)?)
+        }
+        ActionTable::Exec {
+            program,
+            args,
+            cwd,
+            env,
+            window,
+            wait,
+        } => {
+            if program.trim().is_empty() {
+                return Err("exec requires a program name".to_string());
+            }
+            Action::Exec(ExecSpec {
+                program,
+                args,
+                cwd,
+                env,
+                window: parse_window_mode(window.as_deref())?,
+                wait: wait.unwrap_or(false),
+            })
+        }
+        ActionTable::Sequence { steps, delay_ms } => {
+            if steps.is_empty() {
+                return Err("sequence requires at least one step".to_string());
+            }
+            let steps = steps
+                .into_iter()
+                .enumerate()
+                .map(|(index, step)| {
+                    parse_action_config(step).map_err(|e| format!("steps[{index}] {e}"))
+                })
+                .collect::<Result<_, _>>()?;
+            Action::Sequence {
+                steps,
+                delay: Duration::from_millis(delay_ms.unwrap_or(0)),
+            }
+        }
+    };
+    Ok(action)
+}
+
+fn parse_window_mode(value: Option<&str>) -> Result<WindowMode, String> {
     let Some(value) = value else {
-        return Ok(ShiftRequirement::Any);
+        return Ok(WindowMode::Normal);
     };
 
     match value.trim().to_ascii_lowercase().as_str() {
-        "any" => Ok(ShiftRequirement::Any),
-        "down" | "pressed" => Ok(ShiftRequirement::Down),
-        "up" | "released" => Ok(ShiftRequirement::Up),
+        "normal" => Ok(WindowMode::Normal),
+        "hidden" => Ok(WindowMode::Hidden),
+        "minimized" => Ok(WindowMode::Minimized),
         other => Err(format!(
-            "unsupported shift mode '{other}', expected any/down/up"
+            "unsupported window mode '{other}', expected normal/hidden/minimized"
         )),
     }
 }
 
+/// 将窗口状态转换为字符串表示, `normal` 省略不写
+fn window_mode_to_string(mode: WindowMode) -> Option<String> {
+    match mode {
+        WindowMode::Normal => None,
+        WindowMode::Hidden => Some("hidden".to_string()),
+        WindowMode::Minimized => Some("minimized".to_string()),
+    }
+}
+
+/// 解析旧版的 `name` 或 `name:params` 字符串, 只有 action 名称不区分大小写
 fn parse_action(value: &str) -> Result<Action, String> {
-    let trimmed = value.trim().to_ascii_lowercase();
+    let trimmed = value.trim();
 
     // 简单的不带参数的 action
-    if let Ok(action) = parse_simple_action(&trimmed) {
+    if let Ok(action) = parse_simple_action(&trimmed.to_ascii_lowercase()) {
         return Ok(action);
     }
 
-    // 带参数的 action: 按冒号分割
-    if let Some(colon_pos) = trimmed.find(':') {
-        let action_name = &trimmed[..colon_pos];
-        let params = &trimmed[colon_pos + 1..];
+    // 带参数的 action: 按第一个冒号分割
+    if let Some((action_name, params)) = trimmed.split_once(':') {
+        let action_name = action_name.trim().to_ascii_lowercase();
 
-        return match action_name {
+        return match action_name.as_str() {
             "open_program" | "open_app" => {
                 if params.is_empty() {
                     Err("open_program requires a program name".to_string())
                 } else {
                     Ok(Action::OpenProgram {
                         program: params.to_string(),
+                        args: Vec::new(),
                     })
                 }
             }
@@ -570,9 +997,21 @@ fn parse_action(value: &str) -> Result<Action, String> {
                     Ok(Action::SwitchOrOpen {
                         window_title,
                         program,
+                        args: Vec::new(),
                     })
                 }
             }
+            "snap" => Snap::parse(params).map(|snap| Action::Window(WindowCommand::Snap(snap))),
+            "move_to_monitor" => MonitorTarget::parse(params)
+                .map(|target| Action::Window(WindowCommand::MoveToMonitor(target))),
+            "send_keys" => KeyCombo::parse(params).map(Action::SendKeys),
+            "type_text" => {
+                if params.is_empty() {
+                    Err("type_text requires non-empty text".to_string())
+                } else {
+                    Snippet::parse(params).map(Action::TypeText)
+                }
+            }
             _other => Err(format!("unsupported parameterized action '{action_name}'")),
         };
     }
@@ -590,93 +1029,179 @@ fn parse_simple_action(value: &str) -> Result<Action, String> {
         "switch_down" => Ok(Action::SwitchTo(Direction::Down)),
         "scroll_up" => Ok(Action::Scroll(1)),
         "scroll_down" => Ok(Action::Scroll(-1)),
+        "maximize" => Ok(Action::Window(WindowCommand::Maximize)),
+        "minimize" => Ok(Action::Window(WindowCommand::Minimize)),
+        "restore" => Ok(Action::Window(WindowCommand::Restore)),
+        "close" => Ok(Action::Window(WindowCommand::Close)),
+        "toggle_topmost" => Ok(Action::Window(WindowCommand::ToggleTopmost)),
         _ => Err(format!("unknown simple action '{value}'")),
     }
 }
 
-fn parse_key(value: &str) -> Option<Key> {
-    let normalized = value.trim().to_ascii_lowercase();
-
-    let key = match normalized.as_str() {
-        "left" | "leftarrow" => Key::LeftArrow,
-        "right" | "rightarrow" => Key::RightArrow,
-        "up" | "uparrow" => Key::UpArrow,
-        "down" | "downarrow" => Key::DownArrow,
-        "space" => Key::Space,
-        "enter" | "return" => Key::Return,
-        "tab" => Key::Tab,
-        "esc" | "escape" => Key::Escape,
-        "backspace" => Key::Backspace,
-        "home" => Key::Home,
-        "end" => Key::End,
-        "pageup" => Key::PageUp,
-        "pagedown" => Key::PageDown,
-        "insert" => Key::Insert,
-        "delete" => Key::Delete,
-        "f1" => Key::F1,
-        "f2" => Key::F2,
-        "f3" => Key::F3,
-        "f4" => Key::F4,
-        "f5" => Key::F5,
-        "f6" => Key::F6,
-        "f7" => Key::F7,
-        "f8" => Key::F8,
-        "f9" => Key::F9,
-        "f10" => Key::F10,
-        "f11" => Key::F11,
-        "f12" => Key::F12,
-        _ => {
-            if normalized.len() == 1 {
-                let ch = normalized.chars().next()?;
-                return parse_single_char_key(ch);
-            }
-            return None;
-        }
-    };
+#[cfg(test)]
+mod tests {
+    use rdev::Key;
 
-    Some(key)
-}
-
-fn parse_single_char_key(ch: char) -> Option<Key> {
-    let key = match ch {
-        'a' => Key::KeyA,
-        'b' => Key::KeyB,
-        'c' => Key::KeyC,
-        'd' => Key::KeyD,
-        'e' => Key::KeyE,
-        'f' => Key::KeyF,
-        'g' => Key::KeyG,
-        'h' => Key::KeyH,
-        'i' => Key::KeyI,
-        'j' => Key::KeyJ,
-        'k' => Key::KeyK,
-        'l' => Key::KeyL,
-        'm' => Key::KeyM,
-        'n' => Key::KeyN,
-        'o' => Key::KeyO,
-        'p' => Key::KeyP,
-        'q' => Key::KeyQ,
-        'r' => Key::KeyR,
-        's' => Key::KeyS,
-        't' => Key::KeyT,
-        'u' => Key::KeyU,
-        'v' => Key::KeyV,
-        'w' => Key::KeyW,
-        'x' => Key::KeyX,
-        'y' => Key::KeyY,
-        'z' => Key::KeyZ,
-        '0' => Key::Num0,
-        '1' => Key::Num1,
-        '2' => Key::Num2,
-        '3' => Key::Num3,
-        '4' => Key::Num4,
-        '5' => Key::Num5,
-        '6' => Key::Num6,
-        '7' => Key::Num7,
-        '8' => Key::Num8,
-        '9' => Key::Num9,
-        _ => return None,
+    use super::{
+        Action, ActionConfig, ActionTable, Direction, Layer, build_config_from_file,
+        config_to_file, default_hotkey_config, parse_action, parse_action_config,
     };
+    use crate::app::state::{ModifierState, RIGHT_WIN};
+
+    fn caps_layer(bindings: &str) -> Layer {
+        let source = format!("[caps]\n{bindings}");
+        let mut config = build_config_from_file(toml::from_str(&source).unwrap()).unwrap();
+        config.layers.remove(0)
+    }
+
+    fn holding(keys: &[Key]) -> ModifierState {
+        let mut state = ModifierState::default();
+        for key in keys {
+            state.update(*key, true);
+        }
+        state
+    }
+
+    fn resolved(layer: &Layer, modifiers: ModifierState) -> Option<&str> {
+        layer
+            .resolve(Key::KeyH, modifiers)
+            .map(|rule| rule.description.as_str())
+    }
+
+    #[test]
+    fn most_specific_rule_wins_regardless_of_order() {
+        let any = "[[caps.bindings]]\nkey = \"h\"\naction = \"switch_left\"\n";
+        let ctrl = "[[caps.bindings]]\nkey = \"h\"\nctrl = \"down\"\naction = \"switch_right\"\n";
+
+        for layer in [
+            caps_layer(&format!("{any}{ctrl}")),
+            caps_layer(&format!("{ctrl}{any}")),
+        ] {
+            assert_eq!(resolved(&layer, holding(&[])), Some("caps+h"));
+            assert_eq!(
+                resolved(&layer, holding(&[Key::ControlLeft])),
+                Some("caps+ctrl+h")
+            );
+            assert_eq!(
+                resolved(&layer, holding(&[Key::ControlRight])),
+                Some("caps+ctrl+h")
+            );
+        }
+    }
+
+    #[test]
+    fn equally_specific_rules_keep_the_earlier_one() {
+        let layer = caps_layer(
+            r#"
+                [[caps.bindings]]
+                key = "h"
+                ctrl = "down"
+                action = "switch_left"
+
+                [[caps.bindings]]
+                key = "h"
+                alt = "down"
+                action = "switch_right"
+            "#,
+        );
+
+        let rule = layer
+            .resolve(Key::KeyH, holding(&[Key::ControlLeft, Key::Alt]))
+            .unwrap();
+        assert!(matches!(rule.action, Action::SwitchTo(Direction::Left)));
+    }
+
+    #[test]
+    fn up_requirements_exclude_held_modifiers() {
+        for (name, key) in [
+            ("rshift", Key::ShiftRight),
+            ("ctrl", Key::ControlLeft),
+            ("alt", Key::Alt),
+            ("win", Key::MetaLeft),
+        ] {
+            let layer = caps_layer(&format!(
+                "[[caps.bindings]]\nkey = \"h\"\n{name} = \"up\"\naction = \"switch_left\"\n"
+            ));
+
+            assert_eq!(resolved(&layer, holding(&[])), Some("caps+h"), "{name}");
+            assert_eq!(resolved(&layer, holding(&[key])), None, "{name}");
+        }
+    }
+
+    #[test]
+    fn right_win_counts_as_win() {
+        let layer = caps_layer(
+            "[[caps.bindings]]\nkey = \"h\"\nwin = \"down\"\naction = \"switch_left\"\n",
+        );
+
+        let held = holding(&[RIGHT_WIN]);
+        assert!(held.rwin);
+        assert_eq!(held.pressed(), [RIGHT_WIN]);
+        assert_eq!(resolved(&layer, held), Some("caps+win+h"));
+        assert_eq!(resolved(&layer, holding(&[])), None);
+    }
+
+    #[test]
+    fn legacy_actions_keep_parameter_case() {
+        let Ok(Action::OpenProgram { program, args }) =
+            parse_action(r"OPEN_PROGRAM:C:\Tools\MyApp.exe")
+        else {
+            panic!("open_program should parse");
+        };
+        assert_eq!(program, r"C:\Tools\MyApp.exe");
+        assert!(args.is_empty());
+
+        let Ok(Action::SwitchWindow { title }) = parse_action("switch_window:Code") else {
+            panic!("switch_window should parse");
+        };
+        assert_eq!(title, "Code");
+
+        let Ok(Action::SwitchOrOpen {
+            window_title,
+            program,
+            ..
+        }) = parse_action(r"switch_or_open:Code:C:\Apps\Code.exe")
+        else {
+            panic!("switch_or_open should parse");
+        };
+        assert_eq!(window_title, "Code");
+        assert_eq!(program, r"C:\Apps\Code.exe");
+    }
 
-    Some(key)
+    #[test]
+    fn structured_switch_or_open_keeps_args() {
+        let table: ActionTable = toml::from_str(
+            r#"
+                type = "switch_or_open"
+                title = "Visual Studio Code"
+                program = 'C:\Apps\Code.exe'
+                args = ["--new-window", "D:\\Work"]
+            "#,
+        )
+        .unwrap();
+
+        let Ok(Action::SwitchOrOpen {
+            window_title,
+            program,
+            args,
+        }) = parse_action_config(ActionConfig::Table(table))
+        else {
+            panic!("switch_or_open should parse");
+        };
+        assert_eq!(window_title, "Visual Studio Code");
+        assert_eq!(program, r"C:\Apps\Code.exe");
+        assert_eq!(args, ["--new-window", r"D:\Work"]);
+    }
+
+    #[test]
+    fn default_config_round_trips_through_toml() {
+        let saved = toml::to_string_pretty(&config_to_file(&default_hotkey_config())).unwrap();
+        let loaded = build_config_from_file(toml::from_str(&saved).unwrap()).unwrap();
+
+        assert_eq!(loaded.rule_count(), default_hotkey_config().rule_count());
+        assert_eq!(
+            toml::to_string_pretty(&config_to_file(&loaded)).unwrap(),
+            saved
+        );
+    }
 }
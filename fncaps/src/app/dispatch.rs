@@ -0,0 +1,440 @@
+use std::time::SystemTime;
+
+use rdev::Key;
+
+use super::config::HotkeyConfig;
+use super::state::{Action, ActiveLayer, State};
+use super::taphold::{KeyDecision, TapHold};
+
+/// 一次按键事件的处理结果
+#[derive(Debug, Default)]
+pub struct Dispatch {
+    /// 是否吞掉原事件
+    pub suppress: bool,
+    /// 交给 worker 执行的动作和触发动作的按键
+    pub actions: Vec<(Action, Key)>,
+    /// 需要补发的按键事件, 已记录在 State::injected 中
+    pub replay: Vec<(Key, bool)>,
+}
+
+impl Dispatch {
+    fn suppressed() -> Self {
+        Self {
+            suppress: true,
+            ..Self::default()
+        }
+    }
+}
+
+/// 根据当前状态和配置处理一次按键事件, 不访问全局状态, 也不模拟按键
+pub fn dispatch(
+    state: &mut State,
+    cfg: &HotkeyConfig,
+    key: Key,
+    is_pressing: bool,
+    time: SystemTime,
+) -> Dispatch {
+    let mut actions: Vec<(Action, Key)> = Vec::new();
+    let mut suppress = false;
+    let mut replay = Vec::new();
+
+    if let Some(index) = state
+        .injected
+        .iter()
+        .position(|&injected| injected == (key, is_pressing))
+    {
+        state.injected.remove(index);
+        tracing::trace!(target: "fncaps::hotkey", ?key, pressing = is_pressing, "pass through replayed key");
+        return Dispatch::default();
+    }
+
+    if state.released_trigger == Some(key) {
+        if !is_pressing {
+            state.released_trigger = None;
+        }
+        // 普通字符键的按下事件已经补发, 后续事件直接放行; CapsLock 继续吞掉
+        let types_on_tap = cfg
+            .layer_index(key)
+            .is_some_and(|index| cfg.layers[index].types_on_tap());
+        return Dispatch {
+            suppress: !types_on_tap,
+            ..Dispatch::default()
+        };
+    }
+
+    if state.pending_key == Some(key) {
+        if is_pressing {
+            if state.active_layer.is_some() {
+                tracing::debug!(target: "fncaps::hotkey", ?key, "suppress repeat while key pending");
+                return Dispatch::suppressed();
+            }
+            return Dispatch::default();
+        }
+
+        state.pending_key = None;
+        tracing::trace!(target: "fncaps::hotkey", ?key, "suppress pending key release");
+        return Dispatch::suppressed();
+    }
+
+    // 已有层激活时, 其它层的触发键按普通按键处理
+    let trigger = cfg
+        .layer_index(key)
+        .filter(|&index| match &state.active_layer {
+            Some(active) => active.index == index,
+            None => is_pressing,
+        });
+
+    if let Some(index) = trigger {
+        let layer = &cfg.layers[index];
+        if is_pressing {
+            if state.active_layer.is_none() {
+                state.active_layer = Some(ActiveLayer {
+                    index,
+                    tap_hold: TapHold::new(layer.timing, layer.types_on_tap(), time),
+                });
+                state.operations = false;
+                tracing::debug!(target: "fncaps::hotkey", layer = %layer.name, "layer activated");
+            }
+        } else if let Some(active) = state.active_layer.take() {
+            let release = active.tap_hold.released(time);
+            tracing::debug!(target: "fncaps::hotkey", layer = %layer.name, tap = release.tap, "layer released");
+            if release.tap && !state.operations {
+                let action = layer.tap_action.clone();
+                if layer.types_on_tap() {
+                    replay.extend([(key, true), (key, false)]);
+                }
+                tracing::info!(target: "fncaps::hotkey", layer = %layer.name, ?action, "layer tap action from config");
+                actions.push((action, key));
+            }
+            // 暂存的按键没有等到组合, 在触发键之后补发
+            replay.extend(release.waiting.map(|waiting| (waiting, true)));
+        }
+        suppress = true;
+    } else if state.modifiers.update(key, is_pressing) {
+        // 修饰键会取消 CapsLock 的单击动作, 但空格等触发键单击时仍要输出原字符
+        if !state
+            .active_layer
+            .as_ref()
+            .is_some_and(|active| cfg.layers[active.index].types_on_tap())
+        {
+            state.operations = true;
+        }
+        tracing::trace!(target: "fncaps::hotkey", ?key, pressing = is_pressing, "modifier state changed");
+        return Dispatch::default();
+    } else if let Some(active) = state.active_layer.as_mut() {
+        let layer = &cfg.layers[active.index];
+        let rule = layer.resolve(key, state.modifiers);
+        if is_pressing {
+            // 按层的绑定处理的按键, 暂存的按键排在当前按键之前
+            let mut hold = Vec::new();
+            let roll = match rule {
+                Some(rule) => match active.tap_hold.key_pressed(key, time) {
+                    KeyDecision::Hold => {
+                        hold.push((key, rule));
+                        false
+                    }
+                    KeyDecision::HoldWaiting(waiting) => {
+                        // 修饰键变化后暂存的按键可能不再有绑定, 按原按键补发
+                        match layer.resolve(waiting, state.modifiers) {
+                            Some(waiting_rule) => hold.push((waiting, waiting_rule)),
+                            None => replay.push((waiting, true)),
+                        }
+                        hold.push((key, rule));
+                        false
+                    }
+                    KeyDecision::Wait => {
+                        suppress = true;
+                        tracing::debug!(target: "fncaps::hotkey", layer = %layer.name, ?key, "wait for chord before hold delay");
+                        false
+                    }
+                    KeyDecision::Tap => true,
+                },
+                None => layer.types_on_tap() && !state.operations,
+            };
+
+            if roll {
+                // 视为连续打字: 触发键按单击处理, 再按原顺序补发暂存的按键和当前按键, 保证字符顺序不变
+                let waiting = active.tap_hold.waiting();
+                state.active_layer = None;
+                state.released_trigger = Some(layer.trigger);
+                if !state.operations {
+                    actions.push((layer.tap_action.clone(), key));
+                }
+                if layer.types_on_tap() {
+                    replay.push((layer.trigger, true));
+                }
+                replay.extend(waiting.map(|waiting| (waiting, true)));
+                if !replay.is_empty() {
+                    replay.push((key, true));
+                    suppress = true;
+                }
+                tracing::debug!(target: "fncaps::hotkey", layer = %layer.name, ?key, "layer falls back to typing");
+            }
+
+            for &(key, rule) in &hold {
+                if rule.pending {
+                    state.pending_key = Some(key);
+                }
+                state.operations = true;
+                actions.push((rule.action.clone(), key));
+                tracing::info!(
+                    target: "fncaps::hotkey",
+                    ?key,
+                    ?rule.action,
+                    pending = rule.pending,
+                    suppress = rule.suppress,
+                    rule = %rule.description,
+                    "matched configured layer binding"
+                );
+            }
+            match hold.as_slice() {
+                [(_, rule)] if replay.is_empty() => suppress = rule.suppress,
+                [] => {}
+                _ => {
+                    // 暂存的按键已被吞掉, 不吞掉的按键按原顺序补发
+                    replay.extend(
+                        hold.iter()
+                            .filter(|(_, rule)| !rule.suppress)
+                            .map(|&(key, _)| (key, true)),
+                    );
+                    suppress = true;
+                }
+            }
+        } else if active.tap_hold.key_released(key) {
+            // 暂存的按键先于触发键松开, 按组合键处理
+            if let Some(rule) = rule {
+                state.operations = true;
+                actions.push((rule.action.clone(), key));
+                // 按下事件暂存时被吞掉, 绑定不吞掉按键时补发按下和松开
+                if !rule.suppress {
+                    replay.extend([(key, true), (key, false)]);
+                }
+                tracing::info!(
+                    target: "fncaps::hotkey",
+                    ?key,
+                    ?rule.action,
+                    rule = %rule.description,
+                    "matched configured layer binding on release"
+                );
+            }
+            suppress = true;
+        }
+    }
+
+    state.injected.extend(replay.iter().copied());
+
+    actions.retain(|(action, _)| !matches!(action, Action::None));
+    if actions.is_empty() && is_pressing {
+        tracing::trace!(target: "fncaps::hotkey", ?key, "pass-through key event");
+    }
+    Dispatch {
+        suppress,
+        actions,
+        replay,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use rdev::Key;
+
+    use super::{Dispatch, dispatch};
+    use crate::app::config::{HotkeyConfig, parse_hotkey_config};
+    use crate::app::state::{Action, Direction, State};
+
+    const SEMICOLON_LAYER: &str = r#"
+        [layers.semicolon]
+        trigger = ";"
+
+        [[layers.semicolon.bindings]]
+        key = "j"
+        action = { type = "switch_down" }
+    "#;
+
+    const PERMISSIVE_CAPS_LAYER: &str = r#"
+        [layers.caps]
+        trigger = "capslock"
+        hold_delay_ms = 120
+        permissive_hold = true
+
+        [[layers.caps.bindings]]
+        key = "h"
+        action = { type = "switch_left" }
+
+        [[layers.caps.bindings]]
+        key = "j"
+        action = { type = "switch_down" }
+    "#;
+
+    struct Keyboard {
+        state: State,
+        cfg: HotkeyConfig,
+    }
+
+    impl Keyboard {
+        fn new(config: &str) -> Self {
+            Self {
+                state: State::new(),
+                cfg: parse_hotkey_config(config).unwrap(),
+            }
+        }
+
+        fn press(&mut self, key: Key, ms: u64) -> Dispatch {
+            self.event(key, true, ms)
+        }
+
+        fn release(&mut self, key: Key, ms: u64) -> Dispatch {
+            self.event(key, false, ms)
+        }
+
+        fn event(&mut self, key: Key, is_pressing: bool, ms: u64) -> Dispatch {
+            let time = SystemTime::UNIX_EPOCH + Duration::from_millis(ms);
+            dispatch(&mut self.state, &self.cfg, key, is_pressing, time)
+        }
+
+        /// 补发的事件回到钩子时都应被放行
+        fn echo(&mut self, result: &Dispatch, ms: u64) {
+            for &(key, is_pressing) in &result.replay {
+                let echoed = self.event(key, is_pressing, ms);
+                assert!(!echoed.suppress, "{key:?} {is_pressing}");
+                assert!(echoed.actions.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn semicolon_then_unbound_key_types_both() {
+        let mut keyboard = Keyboard::new(SEMICOLON_LAYER);
+
+        assert!(keyboard.press(Key::SemiColon, 0).suppress);
+        let result = keyboard.press(Key::Space, 10);
+        assert!(result.suppress);
+        assert!(result.actions.is_empty());
+        assert_eq!(
+            result.replay,
+            vec![(Key::SemiColon, true), (Key::Space, true)]
+        );
+        keyboard.echo(&result, 11);
+
+        assert!(!keyboard.release(Key::SemiColon, 20).suppress);
+        assert!(!keyboard.release(Key::Space, 30).suppress);
+        assert!(keyboard.state.injected.is_empty());
+        assert!(keyboard.state.active_layer.is_none());
+    }
+
+    #[test]
+    fn semicolon_j_is_a_chord() {
+        let mut keyboard = Keyboard::new(SEMICOLON_LAYER);
+
+        keyboard.press(Key::SemiColon, 0);
+        let result = keyboard.press(Key::KeyJ, 10);
+        assert!(result.suppress);
+        assert!(matches!(
+            result.actions.as_slice(),
+            [(Action::SwitchTo(Direction::Down), Key::KeyJ)]
+        ));
+        assert!(result.replay.is_empty());
+
+        assert!(keyboard.release(Key::KeyJ, 20).suppress);
+        let result = keyboard.release(Key::SemiColon, 30);
+        assert!(result.suppress);
+        assert!(result.actions.is_empty());
+        assert!(result.replay.is_empty());
+    }
+
+    #[test]
+    fn shift_semicolon_still_types_colon() {
+        let mut keyboard = Keyboard::new(SEMICOLON_LAYER);
+
+        assert!(!keyboard.press(Key::ShiftLeft, 0).suppress);
+        assert!(keyboard.press(Key::SemiColon, 10).suppress);
+        let result = keyboard.release(Key::SemiColon, 20);
+        assert!(result.suppress);
+        assert!(result.actions.is_empty());
+        // Shift 仍按着, 补发的分号输出为冒号
+        assert_eq!(
+            result.replay,
+            vec![(Key::SemiColon, true), (Key::SemiColon, false)]
+        );
+        assert!(keyboard.state.modifiers.lshift);
+        keyboard.echo(&result, 21);
+
+        assert!(!keyboard.release(Key::ShiftLeft, 30).suppress);
+        assert!(keyboard.state.injected.is_empty());
+    }
+
+    #[test]
+    fn caps_chord_released_inside_hold_delay_is_a_hold() {
+        let mut keyboard = Keyboard::new(PERMISSIVE_CAPS_LAYER);
+
+        assert!(keyboard.press(Key::CapsLock, 0).suppress);
+        let result = keyboard.press(Key::KeyH, 10);
+        assert!(result.suppress);
+        assert!(result.actions.is_empty());
+
+        let result = keyboard.release(Key::KeyH, 30);
+        assert!(result.suppress);
+        assert!(matches!(
+            result.actions.as_slice(),
+            [(Action::SwitchTo(Direction::Left), Key::KeyH)]
+        ));
+
+        let result = keyboard.release(Key::CapsLock, 40);
+        assert!(result.suppress);
+        assert!(result.actions.is_empty());
+    }
+
+    #[test]
+    fn caps_released_first_switches_ime_and_replays_waiting_key() {
+        let mut keyboard = Keyboard::new(PERMISSIVE_CAPS_LAYER);
+
+        keyboard.press(Key::CapsLock, 0);
+        assert!(keyboard.press(Key::KeyH, 10).suppress);
+        let result = keyboard.release(Key::CapsLock, 50);
+        assert!(result.suppress);
+        assert!(matches!(
+            result.actions.as_slice(),
+            [(Action::SwitchIme, Key::CapsLock)]
+        ));
+        assert_eq!(result.replay, vec![(Key::KeyH, true)]);
+        keyboard.echo(&result, 51);
+
+        assert!(!keyboard.release(Key::KeyH, 60).suppress);
+    }
+
+    #[test]
+    fn caps_second_key_inside_hold_delay_holds_both() {
+        let mut keyboard = Keyboard::new(PERMISSIVE_CAPS_LAYER);
+
+        keyboard.press(Key::CapsLock, 0);
+        keyboard.press(Key::KeyH, 10);
+        let result = keyboard.press(Key::KeyJ, 20);
+        assert!(result.suppress);
+        assert!(matches!(
+            result.actions.as_slice(),
+            [
+                (Action::SwitchTo(Direction::Left), Key::KeyH),
+                (Action::SwitchTo(Direction::Down), Key::KeyJ)
+            ]
+        ));
+        assert!(result.replay.is_empty());
+
+        let result = keyboard.release(Key::CapsLock, 40);
+        assert!(result.actions.is_empty());
+    }
+
+    #[test]
+    fn caps_key_after_hold_delay_is_a_hold() {
+        let mut keyboard = Keyboard::new(PERMISSIVE_CAPS_LAYER);
+
+        keyboard.press(Key::CapsLock, 0);
+        let result = keyboard.press(Key::KeyH, 200);
+        assert!(result.suppress);
+        assert!(matches!(
+            result.actions.as_slice(),
+            [(Action::SwitchTo(Direction::Left), Key::KeyH)]
+        ));
+    }
+}
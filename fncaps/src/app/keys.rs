@@ -0,0 +1,234 @@
+use rdev::Key;
+
+use super::state::RIGHT_WIN;
+
+/// 按键名称表, 解析和序列化共用
+///
+/// 每项的第一个名称用于写入配置文件, 其余名称只在解析时作为别名接受.
+/// rdev 没有对应变体的按键 (F13-F24, 媒体键, 右 Win) 以 Windows 虚拟键码表示为 `Key::Unknown`.
+/// Windows 钩子不会产生的变体 (Fn、小键盘回车) 不在表中, 以免绑定永远无法触发.
+const KEY_NAMES: &[(Key, &[&str])] = &[
+    (Key::KeyA, &["a"]),
+    (Key::KeyB, &["b"]),
+    (Key::KeyC, &["c"]),
+    (Key::KeyD, &["d"]),
+    (Key::KeyE, &["e"]),
+    (Key::KeyF, &["f"]),
+    (Key::KeyG, &["g"]),
+    (Key::KeyH, &["h"]),
+    (Key::KeyI, &["i"]),
+    (Key::KeyJ, &["j"]),
+    (Key::KeyK, &["k"]),
+    (Key::KeyL, &["l"]),
+    (Key::KeyM, &["m"]),
+    (Key::KeyN, &["n"]),
+    (Key::KeyO, &["o"]),
+    (Key::KeyP, &["p"]),
+    (Key::KeyQ, &["q"]),
+    (Key::KeyR, &["r"]),
+    (Key::KeyS, &["s"]),
+    (Key::KeyT, &["t"]),
+    (Key::KeyU, &["u"]),
+    (Key::KeyV, &["v"]),
+    (Key::KeyW, &["w"]),
+    (Key::KeyX, &["x"]),
+    (Key::KeyY, &["y"]),
+    (Key::KeyZ, &["z"]),
+    (Key::Num0, &["0"]),
+    (Key::Num1, &["1"]),
+    (Key::Num2, &["2"]),
+    (Key::Num3, &["3"]),
+    (Key::Num4, &["4"]),
+    (Key::Num5, &["5"]),
+    (Key::Num6, &["6"]),
+    (Key::Num7, &["7"]),
+    (Key::Num8, &["8"]),
+    (Key::Num9, &["9"]),
+    (Key::SemiColon, &[";", "semicolon"]),
+    (Key::Quote, &["'", "quote"]),
+    (Key::Comma, &[",", "comma"]),
+    (Key::Dot, &[".", "dot", "period"]),
+    (Key::Slash, &["/", "slash"]),
+    (Key::BackSlash, &["backslash", "\\"]),
+    (Key::IntlBackslash, &["intlbackslash"]),
+    (Key::LeftBracket, &["[", "leftbracket"]),
+    (Key::RightBracket, &["]", "rightbracket"]),
+    (Key::BackQuote, &["`", "backquote", "grave"]),
+    (Key::Minus, &["-", "minus"]),
+    (Key::Equal, &["=", "equal"]),
+    (Key::LeftArrow, &["left", "leftarrow"]),
+    (Key::RightArrow, &["right", "rightarrow"]),
+    (Key::UpArrow, &["up", "uparrow"]),
+    (Key::DownArrow, &["down", "downarrow"]),
+    (Key::Space, &["space"]),
+    (Key::Return, &["enter", "return"]),
+    (Key::Tab, &["tab"]),
+    (Key::Escape, &["esc", "escape"]),
+    (Key::Backspace, &["backspace"]),
+    (Key::Home, &["home"]),
+    (Key::End, &["end"]),
+    (Key::PageUp, &["pageup"]),
+    (Key::PageDown, &["pagedown"]),
+    (Key::Insert, &["insert"]),
+    (Key::Delete, &["delete"]),
+    (Key::CapsLock, &["capslock"]),
+    (Key::ShiftLeft, &["lshift", "shiftleft"]),
+    (Key::ShiftRight, &["rshift", "shiftright"]),
+    (Key::ControlLeft, &["lctrl", "controlleft"]),
+    (Key::ControlRight, &["rctrl", "controlright"]),
+    (Key::Alt, &["alt", "lalt"]),
+    (Key::AltGr, &["altgr", "ralt"]),
+    (Key::MetaLeft, &["lwin", "metaleft"]),
+    (RIGHT_WIN, &["rwin", "metaright"]),
+    (Key::PrintScreen, &["printscreen", "prtsc"]),
+    (Key::ScrollLock, &["scrolllock"]),
+    (Key::Pause, &["pause"]),
+    (Key::NumLock, &["numlock"]),
+    (Key::Kp0, &["numpad0", "kp0"]),
+    (Key::Kp1, &["numpad1", "kp1"]),
+    (Key::Kp2, &["numpad2", "kp2"]),
+    (Key::Kp3, &["numpad3", "kp3"]),
+    (Key::Kp4, &["numpad4", "kp4"]),
+    (Key::Kp5, &["numpad5", "kp5"]),
+    (Key::Kp6, &["numpad6", "kp6"]),
+    (Key::Kp7, &["numpad7", "kp7"]),
+    (Key::Kp8, &["numpad8", "kp8"]),
+    (Key::Kp9, &["numpad9", "kp9"]),
+    (Key::KpMinus, &["numpad_minus", "kpminus"]),
+    (Key::KpPlus, &["numpad_plus", "kpplus"]),
+    (Key::KpMultiply, &["numpad_multiply", "kpmultiply"]),
+    (Key::KpDivide, &["numpad_divide", "kpdivide"]),
+    (Key::KpDelete, &["numpad_delete", "kpdelete"]),
+    (Key::F1, &["f1"]),
+    (Key::F2, &["f2"]),
+    (Key::F3, &["f3"]),
+    (Key::F4, &["f4"]),
+    (Key::F5, &["f5"]),
+    (Key::F6, &["f6"]),
+    (Key::F7, &["f7"]),
+    (Key::F8, &["f8"]),
+    (Key::F9, &["f9"]),
+    (Key::F10, &["f10"]),
+    (Key::F11, &["f11"]),
+    (Key::F12, &["f12"]),
+    (Key::Unknown(0x7C), &["f13"]),
+    (Key::Unknown(0x7D), &["f14"]),
+    (Key::Unknown(0x7E), &["f15"]),
+    (Key::Unknown(0x7F), &["f16"]),
+    (Key::Unknown(0x80), &["f17"]),
+    (Key::Unknown(0x81), &["f18"]),
+    (Key::Unknown(0x82), &["f19"]),
+    (Key::Unknown(0x83), &["f20"]),
+    (Key::Unknown(0x84), &["f21"]),
+    (Key::Unknown(0x85), &["f22"]),
+    (Key::Unknown(0x86), &["f23"]),
+    (Key::Unknown(0x87), &["f24"]),
+    (Key::Unknown(0xAD), &["volume_mute"]),
+    (Key::Unknown(0xAE), &["volume_down"]),
+    (Key::Unknown(0xAF), &["volume_up"]),
+    (Key::Unknown(0xB0), &["media_next"]),
+    (Key::Unknown(0xB1), &["media_prev"]),
+    (Key::Unknown(0xB2), &["media_stop"]),
+    (Key::Unknown(0xB3), &["media_play_pause"]),
+];
+
+/// 表中没有的虚拟键码写作 `vk:0x..`
+const VK_PREFIX: &str = "vk:";
+
+pub fn parse_key(value: &str) -> Option<Key> {
+    let normalized = value.trim().to_ascii_lowercase();
+
+    if let Some(code) = normalized.strip_prefix(VK_PREFIX) {
+        let code = match code.strip_prefix("0x") {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => code.parse().ok()?,
+        };
+        return Some(Key::Unknown(code));
+    }
+
+    KEY_NAMES
+        .iter()
+        .find(|(_, names)| names.contains(&normalized.as_str()))
+        .map(|(key, _)| *key)
+}
+
+pub fn key_to_string(key: Key) -> String {
+    if let Some((_, names)) = KEY_NAMES.iter().find(|(known, _)| *known == key) {
+        return names[0].to_string();
+    }
+
+    match key {
+        Key::Unknown(code) => format!("{VK_PREFIX}{code:#04x}"),
+        // KEY_NAMES 覆盖了除 Unknown 以外的全部变体
+        other => format!("{other:?}").to_ascii_lowercase(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rdev::Key;
+
+    use super::{KEY_NAMES, key_to_string, parse_key};
+    use crate::app::state::RIGHT_WIN;
+
+    /// 生成 rdev::Key 全部变体的列表, rdev 新增变体时 match 不再完整, 测试无法编译
+    macro_rules! every_key {
+        ($($variant:ident),* $(,)?) => {{
+            fn exhaustive(key: Key) {
+                match key {
+                    $(Key::$variant)|* | Key::Unknown(_) => {}
+                }
+            }
+            let _ = exhaustive;
+            [$(Key::$variant),*]
+        }};
+    }
+
+    #[test]
+    fn every_key_round_trips() {
+        let keys = every_key! {
+            Alt, AltGr, Backspace, CapsLock, ControlLeft, ControlRight, Delete, DownArrow, End,
+            Escape, F1, F10, F11, F12, F2, F3, F4, F5, F6, F7, F8, F9, Home, LeftArrow, MetaLeft,
+            MetaRight, PageDown, PageUp, Return, RightArrow, ShiftLeft, ShiftRight, Space, Tab,
+            UpArrow, PrintScreen, ScrollLock, Pause, NumLock, BackQuote, Num1, Num2, Num3, Num4,
+            Num5, Num6, Num7, Num8, Num9, Num0, Minus, Equal, KeyQ, KeyW, KeyE, KeyR, KeyT, KeyY,
+            KeyU, KeyI, KeyO, KeyP, LeftBracket, RightBracket, KeyA, KeyS, KeyD, KeyF, KeyG, KeyH,
+            KeyJ, KeyK, KeyL, SemiColon, Quote, BackSlash, IntlBackslash, KeyZ, KeyX, KeyC, KeyV,
+            KeyB, KeyN, KeyM, Comma, Dot, Slash, Insert, KpReturn, KpMinus, KpPlus, KpMultiply,
+            KpDivide, Kp0, Kp1, Kp2, Kp3, Kp4, Kp5, Kp6, Kp7, Kp8, Kp9, KpDelete, Function,
+        };
+
+        // Windows 钩子不会产生这些变体: 右 Win 以 VK_RWIN 表示, Fn 和小键盘回车无法区分
+        let unreachable = [Key::MetaRight, Key::Function, Key::KpReturn];
+        for key in unreachable {
+            assert!(KEY_NAMES.iter().all(|(known, _)| *known != key), "{key:?}");
+        }
+        assert_eq!(parse_key("rwin"), Some(RIGHT_WIN));
+        assert_eq!(parse_key("fn"), None);
+        assert_eq!(parse_key("numpad_enter"), None);
+
+        let keys = keys.into_iter().filter(|key| !unreachable.contains(key));
+        for key in keys.chain([RIGHT_WIN, Key::Unknown(0x7C), Key::Unknown(0xFF)]) {
+            let name = key_to_string(key);
+            assert_eq!(parse_key(&name), Some(key), "{key:?} -> {name}");
+        }
+    }
+
+    #[test]
+    fn names_and_aliases_are_unique() {
+        let mut seen = Vec::new();
+        for (key, names) in KEY_NAMES {
+            assert!(!names.is_empty(), "{key:?} has no name");
+            for name in *names {
+                assert_eq!(*name, name.to_ascii_lowercase(), "{name} must be lowercase");
+                assert!(!seen.contains(name), "duplicate key name {name}");
+                seen.push(*name);
+                assert_eq!(parse_key(&name.to_ascii_uppercase()), Some(*key));
+            }
+        }
+        assert_eq!(parse_key("vk:0x7c"), Some(Key::Unknown(0x7C)));
+        assert_eq!(parse_key("vk:124"), Some(Key::Unknown(0x7C)));
+        assert_eq!(parse_key("vk:zz"), None);
+        assert_eq!(parse_key("hyper"), None);
+    }
+}
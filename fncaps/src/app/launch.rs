@@ -1,18 +1,48 @@
-use std::{io, os::windows::process::CommandExt, path::PathBuf, process::Command};
+use std::{
+    collections::BTreeMap,
+    env,
+    ffi::OsString,
+    io, iter,
+    mem::size_of,
+    os::windows::{
+        ffi::OsStrExt,
+        io::{AsRawHandle, FromRawHandle, OwnedHandle},
+        process::{CommandExt, ExitStatusExt},
+    },
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus},
+};
 
-fn open_with_default_app(path: &str) -> io::Result<()> {
+use windows::Win32::Foundation::{BOOL, HANDLE};
+use windows::Win32::System::Threading::{
+    CREATE_UNICODE_ENVIRONMENT, CreateProcessW, GetExitCodeProcess, INFINITE, PROCESS_INFORMATION,
+    STARTF_USESHOWWINDOW, STARTUPINFOW, WaitForSingleObject,
+};
+use windows::Win32::UI::WindowsAndMessaging::SW_SHOWMINNOACTIVE;
+use windows::core::{PCWSTR, PWSTR};
+
+use super::state::{ExecSpec, WindowMode};
+
+const CMD: &str = "C:\\Windows\\system32\\cmd.exe";
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+fn open_with_default_app(path: &str, args: &[String]) -> io::Result<()> {
     let mut path = PathBuf::from(path);
     if !path.is_absolute() {
         path = which::which(path).map_err(|e| io::Error::new(io::ErrorKind::NotFound, e))?;
     }
+    // 带参数时直接启动, 参数不经过 cmd, 避免 & | 等元字符被解释
+    if !args.is_empty() {
+        Command::new(&path).args(args).spawn()?;
+        return Ok(());
+    }
     let Some(path) = path.to_str().map(str::to_string) else {
         return Err(io::Error::new(
             io::ErrorKind::InvalidFilename,
             anyhow::anyhow!("{:?}", path),
         ));
     };
-    const CREATE_NO_WINDOW: u32 = 0x08000000;
-    Command::new("C:\\Windows\\system32\\cmd.exe")
+    Command::new(CMD)
         .creation_flags(CREATE_NO_WINDOW)
         .args(["/C", "start", "", &path])
         .spawn()?;
@@ -20,11 +50,11 @@ fn open_with_default_app(path: &str) -> io::Result<()> {
 }
 
 /// 打开指定的程序或可执行文件
-pub fn open_program(program: &str) {
-    tracing::info!(target: "fncaps::launch", program, "launching program");
+pub fn open_program(program: &str, args: &[String]) {
+    tracing::info!(target: "fncaps::launch", program, ?args, "launching program");
 
     // 尝试直接运行 (可能是 PATH 中的可执行文件或完整路径)
-    match open_with_default_app(program) {
+    match open_with_default_app(program, args) {
         Ok(_) => {
             tracing::debug!(target: "fncaps::launch", program, "program spawned successfully");
         }
@@ -33,3 +63,233 @@ pub fn open_program(program: &str) {
         }
     }
 }
+
+/// 按 exec 动作的参数启动程序
+pub fn exec(spec: &ExecSpec) {
+    tracing::info!(
+        target: "fncaps::launch",
+        program = %spec.program,
+        args = ?spec.args,
+        cwd = ?spec.cwd,
+        env = ?spec.env.keys().collect::<Vec<_>>(),
+        window = ?spec.window,
+        wait = spec.wait,
+        "executing command"
+    );
+
+    match exec_command(spec) {
+        Ok(Some(status)) => {
+            tracing::info!(target: "fncaps::launch", program = %spec.program, %status, "command exited");
+        }
+        Ok(None) => {
+            tracing::debug!(target: "fncaps::launch", program = %spec.program, "command spawned successfully");
+        }
+        Err(e) => {
+            tracing::error!(target: "fncaps::launch", program = %spec.program, error = %e, "failed to execute command");
+        }
+    }
+}
+
+fn exec_command(spec: &ExecSpec) -> io::Result<Option<ExitStatus>> {
+    let program = Path::new(&spec.program);
+    match spawn(spec, program) {
+        // 直接启动找不到时再按 PATH 和 PATHEXT 查找, 例如 code 实际是 code.cmd
+        Err(e) if e.kind() == io::ErrorKind::NotFound && !program.is_absolute() => {
+            let resolved = which::which(program).map_err(|_| e)?;
+            tracing::debug!(target: "fncaps::launch", resolved = %resolved.display(), "program resolved by which");
+            spawn(spec, &resolved)
+        }
+        result => result,
+    }
+}
+
+fn spawn(spec: &ExecSpec, program: &Path) -> io::Result<Option<ExitStatus>> {
+    let mut command = match spec.window {
+        WindowMode::Minimized => return spawn_minimized(spec, program),
+        WindowMode::Hidden => {
+            let mut command = Command::new(program);
+            command.creation_flags(CREATE_NO_WINDOW);
+            command
+        }
+        WindowMode::Normal => Command::new(program),
+    };
+    command.args(&spec.args).envs(&spec.env);
+    if let Some(cwd) = &spec.cwd {
+        command.current_dir(cwd);
+    }
+
+    let mut child = command.spawn()?;
+    if spec.wait {
+        child.wait().map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// std 无法设置 STARTUPINFO 的窗口状态, 最小化启动直接调用 CreateProcessW, 参数不经过 cmd 解释
+fn spawn_minimized(spec: &ExecSpec, program: &Path) -> io::Result<Option<ExitStatus>> {
+    let mut command_line = to_wide(command_line(program, &spec.args));
+    let environment = (!spec.env.is_empty()).then(|| environment_block(env::vars_os(), &spec.env));
+    let cwd = spec.cwd.as_ref().map(to_wide);
+    let startup = STARTUPINFOW {
+        cb: size_of::<STARTUPINFOW>() as u32,
+        dwFlags: STARTF_USESHOWWINDOW,
+        wShowWindow: SW_SHOWMINNOACTIVE.0 as u16,
+        ..Default::default()
+    };
+    let mut info = PROCESS_INFORMATION::default();
+
+    unsafe {
+        CreateProcessW(
+            PCWSTR::null(),
+            PWSTR(command_line.as_mut_ptr()),
+            None,
+            None,
+            BOOL::from(false),
+            CREATE_UNICODE_ENVIRONMENT,
+            environment.as_ref().map(|block| block.as_ptr().cast()),
+            cwd.as_ref()
+                .map_or(PCWSTR::null(), |cwd| PCWSTR(cwd.as_ptr())),
+            &startup,
+            &mut info,
+        )
+    }
+    .map_err(os_error)?;
+
+    // 句柄交给 OwnedHandle, 离开作用域时关闭
+    let (process, _thread) = unsafe {
+        (
+            OwnedHandle::from_raw_handle(info.hProcess.0),
+            OwnedHandle::from_raw_handle(info.hThread.0),
+        )
+    };
+    if !spec.wait {
+        return Ok(None);
+    }
+
+    let handle = HANDLE(process.as_raw_handle());
+    let mut code = 0;
+    unsafe {
+        WaitForSingleObject(handle, INFINITE);
+        GetExitCodeProcess(handle, &mut code)
+    }
+    .map_err(os_error)?;
+    Ok(Some(ExitStatus::from_raw(code)))
+}
+
+/// HRESULT 的低 16 位是 Win32 错误码, 保留 NotFound 等错误类型以便回退到 which 查找
+fn os_error(error: windows::core::Error) -> io::Error {
+    io::Error::from_raw_os_error(error.code().0 & 0xFFFF)
+}
+
+/// 按 CommandLineToArgvW 的规则拼接命令行, 与 std 的 Command 一致
+fn command_line(program: &Path, args: &[String]) -> OsString {
+    let mut line = OsString::from("\"");
+    line.push(program);
+    line.push("\"");
+    for arg in args {
+        line.push(" ");
+        line.push(quote_arg(arg));
+    }
+    line
+}
+
+fn quote_arg(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+        return arg.to_string();
+    }
+
+    let mut quoted = String::from('"');
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        if c == '\\' {
+            backslashes += 1;
+            continue;
+        }
+        // 引号前的反斜杠需要加倍, 引号本身再转义一次
+        let escapes = if c == '"' {
+            backslashes * 2 + 1
+        } else {
+            backslashes
+        };
+        quoted.extend(iter::repeat_n('\\', escapes));
+        quoted.push(c);
+        backslashes = 0;
+    }
+    // 结尾的反斜杠紧跟闭合引号, 同样需要加倍
+    quoted.extend(iter::repeat_n('\\', backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+/// base (当前进程的环境变量) 加上 spec.env, 变量名不区分大小写, 按大写的变量名排序
+fn environment_block(
+    base: impl IntoIterator<Item = (OsString, OsString)>,
+    overrides: &BTreeMap<String, String>,
+) -> Vec<u16> {
+    let mut vars: BTreeMap<String, (OsString, OsString)> = base
+        .into_iter()
+        .map(|(name, value)| (name.to_string_lossy().to_uppercase(), (name, value)))
+        .collect();
+    for (name, value) in overrides {
+        vars.insert(name.to_uppercase(), (name.into(), value.into()));
+    }
+
+    let mut block = Vec::new();
+    for (name, value) in vars.into_values() {
+        block.extend(name.encode_wide());
+        block.push(u16::from(b'='));
+        block.extend(value.encode_wide());
+        block.push(0);
+    }
+    block.push(0);
+    block
+}
+
+fn to_wide(value: impl AsRef<std::ffi::OsStr>) -> Vec<u16> {
+    value.as_ref().encode_wide().chain(iter::once(0)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::ffi::OsString;
+    use std::path::Path;
+
+    use super::{command_line, environment_block, quote_arg};
+
+    #[test]
+    fn quotes_args_like_command_line_to_argv() {
+        assert_eq!(quote_arg("plain"), "plain");
+        assert_eq!(quote_arg(""), r#""""#);
+        assert_eq!(quote_arg("a b"), r#""a b""#);
+        // 不需要引号时反斜杠原样保留
+        assert_eq!(quote_arg(r"C:\dir\"), r"C:\dir\");
+        assert_eq!(quote_arg(r"C:\My Dir\"), r#""C:\My Dir\\""#);
+        assert_eq!(quote_arg(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(quote_arg(r#"a\"b"#), r#""a\\\"b""#);
+        assert_eq!(quote_arg(r"a\b c"), r#""a\b c""#);
+    }
+
+    #[test]
+    fn command_line_quotes_program_and_keeps_empty_args() {
+        let line = command_line(
+            Path::new(r"C:\Program Files\app.exe"),
+            &["".to_string(), "x y".to_string(), "-v".to_string()],
+        );
+        assert_eq!(line, r#""C:\Program Files\app.exe" "" "x y" -v"#);
+    }
+
+    #[test]
+    fn environment_overrides_ignore_case_and_sort_names() {
+        let base = [("Path", "a"), ("windir", "w"), ("ZED", "z")]
+            .map(|(name, value)| (OsString::from(name), OsString::from(value)));
+        let overrides = BTreeMap::from([
+            ("PATH".to_string(), "b".to_string()),
+            ("Alpha".to_string(), "1".to_string()),
+        ]);
+
+        let block = String::from_utf16(&environment_block(base, &overrides)).unwrap();
+        assert_eq!(block, "Alpha=1\0PATH=b\0windir=w\0ZED=z\0\0");
+    }
+}
@@ -0,0 +1,405 @@
+use std::fmt;
+
+/// 判断窗口是否已经位于某个三分栏时允许的误差, 终端等程序会把尺寸对齐到字符网格
+const SNAP_TOLERANCE: i32 = 24;
+
+/// 屏幕坐标中的矩形, 与 Win32 的 RECT 相同, right 和 bottom 不属于矩形
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Rect {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl Rect {
+    pub const fn new(left: i32, top: i32, right: i32, bottom: i32) -> Self {
+        Self {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    pub const fn width(&self) -> i32 {
+        self.right - self.left
+    }
+
+    pub const fn height(&self) -> i32 {
+        self.bottom - self.top
+    }
+
+    fn intersection_area(&self, other: &Rect) -> i64 {
+        let width = self.right.min(other.right) - self.left.max(other.left);
+        let height = self.bottom.min(other.bottom) - self.top.max(other.top);
+        if width <= 0 || height <= 0 {
+            return 0;
+        }
+        width as i64 * height as i64
+    }
+
+    /// 两个矩形中心距离的平方
+    fn center_distance_square(&self, other: &Rect) -> i64 {
+        // 使用两倍坐标避免除法取整
+        let dx = (self.left as i64 + self.right as i64) - (other.left as i64 + other.right as i64);
+        let dy = (self.top as i64 + self.bottom as i64) - (other.top as i64 + other.bottom as i64);
+        dx * dx + dy * dy
+    }
+}
+
+/// 显示器区域, work 为去掉任务栏后的工作区
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Monitor {
+    pub bounds: Rect,
+    pub work: Rect,
+}
+
+/// 把窗口吸附到工作区中的位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Snap {
+    LeftHalf,
+    RightHalf,
+    Top,
+    Bottom,
+    /// 保持窗口大小移到工作区中央
+    Center,
+    /// 左、中、右三栏, 重复执行时依次切换
+    Thirds,
+}
+
+const SNAP_NAMES: &[(&str, Snap)] = &[
+    ("left-half", Snap::LeftHalf),
+    ("right-half", Snap::RightHalf),
+    ("top", Snap::Top),
+    ("bottom", Snap::Bottom),
+    ("center", Snap::Center),
+    ("thirds", Snap::Thirds),
+];
+
+impl Snap {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let normalized = value.trim().to_ascii_lowercase();
+        SNAP_NAMES
+            .iter()
+            .find(|(name, _)| *name == normalized)
+            .map(|(_, snap)| *snap)
+            .ok_or_else(|| {
+                format!(
+                    "unsupported snap position '{value}', expected left-half/right-half/top/bottom/center/thirds"
+                )
+            })
+    }
+}
+
+impl fmt::Display for Snap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = SNAP_NAMES
+            .iter()
+            .find(|(_, snap)| snap == self)
+            .map_or("", |(name, _)| name);
+        f.write_str(name)
+    }
+}
+
+/// 把窗口移到哪个显示器, 序号按显示器从左到右、从上到下排列, 从 1 开始
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonitorTarget {
+    Next,
+    Prev,
+    Index(usize),
+}
+
+impl MonitorTarget {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "next" => Ok(Self::Next),
+            "prev" | "previous" => Ok(Self::Prev),
+            index => match index.parse::<usize>() {
+                Ok(index) if index >= 1 => Ok(Self::Index(index)),
+                _ => Err(format!(
+                    "invalid monitor '{value}', expected next, prev or an index starting from 1"
+                )),
+            },
+        }
+    }
+}
+
+impl fmt::Display for MonitorTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Next => f.write_str("next"),
+            Self::Prev => f.write_str("prev"),
+            Self::Index(index) => write!(f, "{index}"),
+        }
+    }
+}
+
+/// 计算吸附后窗口可见区域的位置, window 为窗口当前的可见区域
+pub fn snap_rect(snap: Snap, work: Rect, window: Rect) -> Rect {
+    let middle_x = work.left + work.width() / 2;
+    let middle_y = work.top + work.height() / 2;
+    match snap {
+        Snap::LeftHalf => Rect::new(work.left, work.top, middle_x, work.bottom),
+        Snap::RightHalf => Rect::new(middle_x, work.top, work.right, work.bottom),
+        Snap::Top => Rect::new(work.left, work.top, work.right, middle_y),
+        Snap::Bottom => Rect::new(work.left, middle_y, work.right, work.bottom),
+        Snap::Center => {
+            let width = window.width().min(work.width());
+            let height = window.height().min(work.height());
+            let left = work.left + (work.width() - width) / 2;
+            let top = work.top + (work.height() - height) / 2;
+            Rect::new(left, top, left + width, top + height)
+        }
+        Snap::Thirds => {
+            let current = (0..3).find(|&column| {
+                let third = third_rect(work, column);
+                (third.left - window.left).abs() <= SNAP_TOLERANCE
+                    && (third.right - window.right).abs() <= SNAP_TOLERANCE
+            });
+            third_rect(work, current.map_or(0, |column| (column + 1) % 3))
+        }
+    }
+}
+
+/// 工作区的第 column 栏, 除不尽的像素由后面的栏分担
+fn third_rect(work: Rect, column: i32) -> Rect {
+    let edge = |column: i32| work.left + work.width() * column / 3;
+    Rect::new(edge(column), work.top, edge(column + 1), work.bottom)
+}
+
+/// 按从左到右、从上到下排序显示器, 使 MonitorTarget 的序号稳定
+pub fn sort_monitors(monitors: &mut [Monitor]) {
+    monitors.sort_by_key(|monitor| (monitor.bounds.left, monitor.bounds.top));
+}
+
+/// 窗口所在的显示器: 与窗口重叠面积最大的, 都不重叠时取中心离窗口最近的
+pub fn monitor_of(monitors: &[Monitor], window: Rect) -> Option<usize> {
+    monitors
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, monitor)| {
+            (
+                monitor.bounds.intersection_area(&window),
+                -monitor.bounds.center_distance_square(&window),
+            )
+        })
+        .map(|(index, _)| index)
+}
+
+/// 目标显示器在列表中的下标, next/prev 首尾循环, 序号超出显示器数量时返回 None
+pub fn target_monitor(count: usize, current: usize, target: MonitorTarget) -> Option<usize> {
+    if count == 0 {
+        return None;
+    }
+    match target {
+        MonitorTarget::Next => Some((current + 1) % count),
+        MonitorTarget::Prev => Some((current + count - 1) % count),
+        MonitorTarget::Index(index) => (index <= count).then(|| index - 1),
+    }
+}
+
+/// 把窗口从一个工作区移到另一个工作区, 保持窗口在剩余空间中的相对位置, 尺寸超出时缩小
+pub fn move_between(window: Rect, from: Rect, to: Rect) -> Rect {
+    let (left, width) = move_axis(
+        (window.left, window.width()),
+        (from.left, from.width()),
+        (to.left, to.width()),
+    );
+    let (top, height) = move_axis(
+        (window.top, window.height()),
+        (from.top, from.height()),
+        (to.top, to.height()),
+    );
+    Rect::new(left, top, left + width, top + height)
+}
+
+/// 在一个方向上移动, 参数都是 (起点, 长度)
+fn move_axis(window: (i32, i32), from: (i32, i32), to: (i32, i32)) -> (i32, i32) {
+    let size = window.1.min(to.1);
+    let from_space = (from.1 - window.1) as i64;
+    let to_space = (to.1 - size) as i64;
+    let offset = if from_space > 0 {
+        let offset = ((window.0 - from.0) as i64).clamp(0, from_space);
+        offset * to_space / from_space
+    } else {
+        0
+    };
+    (to.0 + offset as i32, size)
+}
+
+/// 窗口矩形包含不可见的缩放边框, 把按可见区域 frame 计算出的 target 向外扩展相同的距离
+pub fn with_frame(target: Rect, window: Rect, frame: Rect) -> Rect {
+    Rect::new(
+        target.left - (frame.left - window.left),
+        target.top - (frame.top - window.top),
+        target.right + (window.right - frame.right),
+        target.bottom + (window.bottom - frame.bottom),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Monitor, MonitorTarget, Rect, Snap, monitor_of, move_between, snap_rect, sort_monitors,
+        target_monitor, with_frame,
+    };
+
+    /// 主显示器 2560x1440, 左侧 1920x1080 底部对齐, 主显示器上方竖放 1080x1920
+    fn layout() -> Vec<Monitor> {
+        let mut monitors = vec![
+            Monitor {
+                bounds: Rect::new(0, 0, 2560, 1440),
+                work: Rect::new(0, 0, 2560, 1392),
+            },
+            Monitor {
+                bounds: Rect::new(500, -1920, 1580, 0),
+                work: Rect::new(500, -1920, 1580, -48),
+            },
+            Monitor {
+                bounds: Rect::new(-1920, 360, 0, 1440),
+                work: Rect::new(-1920, 360, 0, 1400),
+            },
+        ];
+        sort_monitors(&mut monitors);
+        monitors
+    }
+
+    #[test]
+    fn parses_and_formats_names() {
+        assert_eq!(Snap::parse("Left-Half"), Ok(Snap::LeftHalf));
+        assert_eq!(Snap::parse("thirds").unwrap().to_string(), "thirds");
+        assert!(Snap::parse("left").unwrap_err().contains("left-half"));
+
+        assert_eq!(MonitorTarget::parse("next"), Ok(MonitorTarget::Next));
+        assert_eq!(MonitorTarget::parse("Previous"), Ok(MonitorTarget::Prev));
+        assert_eq!(MonitorTarget::parse(" 2 "), Ok(MonitorTarget::Index(2)));
+        assert_eq!(MonitorTarget::Index(2).to_string(), "2");
+        assert!(MonitorTarget::parse("0").is_err());
+        assert!(MonitorTarget::parse("-1").is_err());
+        assert!(MonitorTarget::parse("left").is_err());
+    }
+
+    #[test]
+    fn snaps_halves_on_negative_coordinates() {
+        let work = Rect::new(-1920, 360, 0, 1400);
+        let window = Rect::new(-1500, 500, -700, 1100);
+
+        assert_eq!(
+            snap_rect(Snap::LeftHalf, work, window),
+            Rect::new(-1920, 360, -960, 1400)
+        );
+        assert_eq!(
+            snap_rect(Snap::RightHalf, work, window),
+            Rect::new(-960, 360, 0, 1400)
+        );
+        assert_eq!(
+            snap_rect(Snap::Top, work, window),
+            Rect::new(-1920, 360, 0, 880)
+        );
+        assert_eq!(
+            snap_rect(Snap::Bottom, work, window),
+            Rect::new(-1920, 880, 0, 1400)
+        );
+    }
+
+    #[test]
+    fn center_keeps_size_and_shrinks_oversized_windows() {
+        let work = Rect::new(500, -1920, 1580, -48);
+        assert_eq!(
+            snap_rect(Snap::Center, work, Rect::new(-100, 0, 700, 600)),
+            Rect::new(640, -1284, 1440, -684)
+        );
+        assert_eq!(
+            snap_rect(Snap::Center, work, Rect::new(0, 0, 2000, 1000)),
+            Rect::new(500, -1484, 1580, -484)
+        );
+    }
+
+    #[test]
+    fn thirds_cycle_through_columns() {
+        let work = Rect::new(-2560, -100, 0, 1292);
+        let first = Rect::new(-2560, -100, -1707, 1292);
+        let second = Rect::new(-1707, -100, -854, 1292);
+        let third = Rect::new(-854, -100, 0, 1292);
+
+        let window = Rect::new(-2000, 200, -1000, 900);
+        assert_eq!(snap_rect(Snap::Thirds, work, window), first);
+        assert_eq!(snap_rect(Snap::Thirds, work, first), second);
+        assert_eq!(snap_rect(Snap::Thirds, work, second), third);
+        assert_eq!(snap_rect(Snap::Thirds, work, third), first);
+
+        // 终端按字符网格对齐后略小于目标, 仍然认为位于该栏
+        let aligned = Rect::new(-1707, -100, -870, 1280);
+        assert_eq!(snap_rect(Snap::Thirds, work, aligned), third);
+    }
+
+    #[test]
+    fn finds_and_targets_monitors() {
+        let monitors = layout();
+        let lefts: Vec<i32> = monitors.iter().map(|m| m.bounds.left).collect();
+        assert_eq!(lefts, [-1920, 0, 500]);
+
+        // 跨越两个显示器时取重叠面积更大的
+        assert_eq!(
+            monitor_of(&monitors, Rect::new(-300, 400, 900, 800)),
+            Some(1)
+        );
+        assert_eq!(
+            monitor_of(&monitors, Rect::new(600, -900, 1200, 100)),
+            Some(2)
+        );
+        // 完全在显示器之外时取最近的
+        assert_eq!(
+            monitor_of(&monitors, Rect::new(-1900, -400, -1500, -100)),
+            Some(0)
+        );
+        assert_eq!(monitor_of(&[], Rect::new(0, 0, 10, 10)), None);
+
+        assert_eq!(target_monitor(3, 2, MonitorTarget::Next), Some(0));
+        assert_eq!(target_monitor(3, 0, MonitorTarget::Prev), Some(2));
+        assert_eq!(target_monitor(3, 0, MonitorTarget::Index(3)), Some(2));
+        assert_eq!(target_monitor(3, 0, MonitorTarget::Index(4)), None);
+        assert_eq!(target_monitor(0, 0, MonitorTarget::Next), None);
+    }
+
+    #[test]
+    fn moves_between_monitors_keeping_relative_position() {
+        let monitors = layout();
+        let primary = monitors[1].work;
+        let left = monitors[0].work;
+        let portrait = monitors[2].work;
+
+        // 贴右边缘的窗口移动后仍然贴右边缘
+        let window = Rect::new(1760, 392, 2560, 992);
+        assert_eq!(
+            move_between(window, primary, left),
+            Rect::new(-800, 577, 0, 1177)
+        );
+
+        // 大于目标工作区的窗口被缩小
+        let window = Rect::new(100, 50, 2100, 1250);
+        assert_eq!(
+            move_between(window, primary, portrait),
+            Rect::new(500, -1745, 1580, -545)
+        );
+
+        // 部分超出原工作区的窗口按边缘处理
+        let window = Rect::new(-2100, 300, -1300, 700);
+        assert_eq!(
+            move_between(window, left, primary),
+            Rect::new(0, 0, 800, 400)
+        );
+    }
+
+    #[test]
+    fn expands_target_by_invisible_frame() {
+        let window = Rect::new(-1927, 360, -953, 1407);
+        let frame = Rect::new(-1920, 360, -960, 1400);
+        let target = Rect::new(-960, 360, 0, 1400);
+        assert_eq!(
+            with_frame(target, window, frame),
+            Rect::new(-967, 360, 7, 1407)
+        );
+    }
+}
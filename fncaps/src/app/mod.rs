@@ -1,10 +1,18 @@
+mod combo;
 mod config;
+mod dispatch;
 mod hotkey;
 mod ime;
+mod keys;
 mod launch;
+mod layout;
 mod logging;
+mod snippet;
 mod state;
+mod taphold;
+mod text_input;
 mod windows_ops;
+mod worker;
 
 pub fn run() {
     logging::init_logging();
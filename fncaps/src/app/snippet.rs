@@ -0,0 +1,220 @@
+use std::fmt;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local};
+use clipboard_rs::{Clipboard, ClipboardContext};
+
+/// `{date}` 未指定格式时使用的格式
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// 展开占位符时读取的外部数据, 测试时可以替换
+pub trait Context {
+    fn now(&self) -> DateTime<Local>;
+    fn clipboard(&self) -> Option<String>;
+    fn env(&self, name: &str) -> Option<String>;
+}
+
+/// 从系统读取时间、剪贴板和环境变量
+pub struct SystemContext;
+
+impl Context for SystemContext {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+
+    fn clipboard(&self) -> Option<String> {
+        let context = ClipboardContext::new()
+            .map_err(|e| tracing::warn!(target: "fncaps::snippet", error = %e, "failed to open clipboard"))
+            .ok()?;
+        context.get_text().ok()
+    }
+
+    fn env(&self, name: &str) -> Option<String> {
+        std::env::var(name).ok()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Date(String),
+    Clipboard,
+    Env(String),
+}
+
+/// 带占位符的文本片段, 例如 `今天是 {date:%Y-%m-%d}`
+///
+/// 支持 `{date}` / `{date:格式}`, `{clipboard}` 和 `{env:变量名}`, `{{` 和 `}}` 表示花括号本身.
+/// 占位符在加载配置时检查, 展开时读取不到的剪贴板或环境变量替换为空字符串.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snippet {
+    template: String,
+    parts: Vec<Part>,
+}
+
+impl Snippet {
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '{' => {
+                    let rest = chars.as_str();
+                    let end = rest
+                        .find('}')
+                        .ok_or_else(|| format!("unclosed placeholder in '{template}'"))?;
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(parse_placeholder(&rest[..end])?);
+                    chars = rest[end + 1..].chars();
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '}' => {
+                    return Err(format!(
+                        "unmatched '}}' in '{template}', write '}}}}' for a literal brace"
+                    ));
+                }
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+
+        Ok(Self {
+            template: template.to_string(),
+            parts,
+        })
+    }
+
+    pub fn expand(&self, context: &impl Context) -> String {
+        let mut output = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => output.push_str(text),
+                Part::Date(format) => output.push_str(&context.now().format(format).to_string()),
+                Part::Clipboard => match context.clipboard() {
+                    Some(text) => output.push_str(&text),
+                    None => tracing::warn!(target: "fncaps::snippet", "clipboard has no text"),
+                },
+                Part::Env(name) => match context.env(name) {
+                    Some(value) => output.push_str(&value),
+                    None => {
+                        tracing::warn!(target: "fncaps::snippet", name = %name, "environment variable not set")
+                    }
+                },
+            }
+        }
+        output
+    }
+}
+
+impl fmt::Display for Snippet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.template)
+    }
+}
+
+fn parse_placeholder(placeholder: &str) -> Result<Part, String> {
+    let (name, arg) = match placeholder.split_once(':') {
+        Some((name, arg)) => (name, Some(arg)),
+        None => (placeholder, None),
+    };
+
+    match (name.trim().to_ascii_lowercase().as_str(), arg) {
+        ("date", format) => {
+            let format = format.unwrap_or(DEFAULT_DATE_FORMAT);
+            if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+                return Err(format!("invalid date format '{format}'"));
+            }
+            Ok(Part::Date(format.to_string()))
+        }
+        ("clipboard", None) => Ok(Part::Clipboard),
+        ("env", Some(var)) if !var.trim().is_empty() => Ok(Part::Env(var.trim().to_string())),
+        _ => Err(format!(
+            "unknown placeholder '{{{placeholder}}}', expected {{date}}, {{date:format}}, {{clipboard}} or {{env:NAME}}"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Local, TimeZone};
+
+    use super::{Context, Snippet};
+
+    struct FakeContext {
+        clipboard: Option<&'static str>,
+    }
+
+    impl Context for FakeContext {
+        fn now(&self) -> DateTime<Local> {
+            Local.with_ymd_and_hms(2026, 10, 18, 9, 5, 0).unwrap()
+        }
+
+        fn clipboard(&self) -> Option<String> {
+            self.clipboard.map(str::to_string)
+        }
+
+        fn env(&self, name: &str) -> Option<String> {
+            (name == "USERNAME").then(|| "alice".to_string())
+        }
+    }
+
+    const CONTEXT: FakeContext = FakeContext {
+        clipboard: Some("剪贴板内容"),
+    };
+
+    #[test]
+    fn expands_placeholders() {
+        let snippet = Snippet::parse("{date} {DATE:%H:%M} by {env:USERNAME}: {clipboard}").unwrap();
+        assert_eq!(
+            snippet.expand(&CONTEXT),
+            "2026-10-18 09:05 by alice: 剪贴板内容"
+        );
+
+        let signature = Snippet::parse("此致\n{env:USERNAME}\n").unwrap();
+        assert_eq!(signature.expand(&CONTEXT), "此致\nalice\n");
+        assert_eq!(signature.to_string(), "此致\n{env:USERNAME}\n");
+    }
+
+    #[test]
+    fn missing_values_expand_to_empty() {
+        let snippet = Snippet::parse("[{clipboard}][{env:NOT_SET}]").unwrap();
+        assert_eq!(snippet.expand(&FakeContext { clipboard: None }), "[][]");
+    }
+
+    #[test]
+    fn doubled_braces_are_literal() {
+        let snippet = Snippet::parse("{{date}} }}{date:%Y}{{").unwrap();
+        assert_eq!(snippet.expand(&CONTEXT), "{date} }2026{");
+    }
+
+    #[test]
+    fn invalid_templates_are_rejected() {
+        assert!(Snippet::parse("{date").unwrap_err().contains("unclosed"));
+        assert!(Snippet::parse("a } b").unwrap_err().contains("unmatched"));
+        assert!(
+            Snippet::parse("{time}")
+                .unwrap_err()
+                .contains("unknown placeholder")
+        );
+        assert!(Snippet::parse("{env:}").is_err());
+        assert!(Snippet::parse("{clipboard:x}").is_err());
+        assert!(
+            Snippet::parse("{date:%Q}")
+                .unwrap_err()
+                .contains("invalid date format")
+        );
+    }
+}
@@ -1,5 +1,12 @@
 use rdev::Key;
+use std::collections::BTreeMap;
 use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+use super::combo::KeyCombo;
+use super::layout::{MonitorTarget, Snap};
+use super::snippet::Snippet;
+use super::taphold::TapHold;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Direction {
@@ -20,25 +27,141 @@ impl Direction {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// 对前台窗口执行的窗口管理操作
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WindowCommand {
+    Maximize,
+    Minimize,
+    Restore,
+    Close,
+    ToggleTopmost,
+    Snap(Snap),
+    MoveToMonitor(MonitorTarget),
+}
+
+/// rdev 在 Windows 上没有右 Win 键的映射, 钩子收到的是虚拟键码 VK_RWIN
+pub const RIGHT_WIN: Key = Key::Unknown(0x5C);
+
+/// 当前各修饰键的按下状态, 左右两侧分别记录
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ModifierState {
+    pub lshift: bool,
+    pub rshift: bool,
+    pub lctrl: bool,
+    pub rctrl: bool,
+    pub lalt: bool,
+    pub ralt: bool,
+    pub lwin: bool,
+    pub rwin: bool,
+}
+
+impl ModifierState {
+    /// 根据按键事件更新状态, key 不是修饰键时返回 false
+    pub fn update(&mut self, key: Key, is_pressing: bool) -> bool {
+        let slot = match key {
+            Key::ShiftLeft => &mut self.lshift,
+            Key::ShiftRight => &mut self.rshift,
+            Key::ControlLeft => &mut self.lctrl,
+            Key::ControlRight => &mut self.rctrl,
+            Key::Alt => &mut self.lalt,
+            Key::AltGr => &mut self.ralt,
+            Key::MetaLeft => &mut self.lwin,
+            RIGHT_WIN => &mut self.rwin,
+            _ => return false,
+        };
+        *slot = is_pressing;
+        true
+    }
+
+    /// 当前按下的修饰键
+    pub fn pressed(self) -> Vec<Key> {
+        [
+            (self.lshift, Key::ShiftLeft),
+            (self.rshift, Key::ShiftRight),
+            (self.lctrl, Key::ControlLeft),
+            (self.rctrl, Key::ControlRight),
+            (self.lalt, Key::Alt),
+            (self.ralt, Key::AltGr),
+            (self.lwin, Key::MetaLeft),
+            (self.rwin, RIGHT_WIN),
+        ]
+        .into_iter()
+        .filter_map(|(pressing, key)| pressing.then_some(key))
+        .collect()
+    }
+
+    pub fn is_modifier(key: Key) -> bool {
+        Self::default().update(key, true)
+    }
+
+    pub fn ctrl(self) -> bool {
+        self.lctrl || self.rctrl
+    }
+
+    pub fn alt(self) -> bool {
+        self.lalt || self.ralt
+    }
+
+    pub fn win(self) -> bool {
+        self.lwin || self.rwin
+    }
+}
+
+/// 当前按住触发键的层
+#[derive(Debug, Clone)]
+pub struct ActiveLayer {
+    /// 层在 HotkeyConfig::layers 中的下标
+    pub index: usize,
+    pub tap_hold: TapHold,
+}
+
+#[derive(Debug, Clone)]
 pub struct State {
-    pub caps_lock_pressing: bool,
-    pub lshift_pressing: bool,
+    pub active_layer: Option<ActiveLayer>,
+    pub modifiers: ModifierState,
     pub pending_key: Option<Key>,
     pub operations: bool,
+    /// 由 fncaps 自己模拟发出, 需要直接放行的按键事件 (key, 是否按下)
+    pub injected: Vec<(Key, bool)>,
+    /// 已经提前按单击处理的触发键, 其后续事件不再激活层
+    pub released_trigger: Option<Key>,
 }
 
 impl State {
     pub fn new() -> Self {
         Self {
-            caps_lock_pressing: false,
-            lshift_pressing: false,
+            active_layer: None,
+            modifiers: ModifierState::default(),
             pending_key: None,
             operations: false,
+            injected: Vec::new(),
+            released_trigger: None,
         }
     }
 }
 
+/// exec 启动程序时的窗口状态
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WindowMode {
+    #[default]
+    Normal,
+    /// 不创建控制台窗口, 适合命令行程序
+    Hidden,
+    Minimized,
+}
+
+/// exec 动作的参数
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecSpec {
+    pub program: String,
+    pub args: Vec<String>,
+    pub cwd: Option<String>,
+    pub env: BTreeMap<String, String>,
+    pub window: WindowMode,
+    /// 是否等待程序退出, 在 sequence 中可以让后续步骤等命令完成
+    pub wait: bool,
+}
+
 #[derive(Debug, Clone)]
 pub enum Action {
     None,
@@ -48,6 +171,7 @@ pub enum Action {
     /// 打开指定程序
     OpenProgram {
         program: String,
+        args: Vec<String>,
     },
     /// 切换到指定窗口标题
     SwitchWindow {
@@ -57,6 +181,20 @@ pub enum Action {
     SwitchOrOpen {
         window_title: String,
         program: String,
+        args: Vec<String>,
+    },
+    /// 模拟按下组合键
+    SendKeys(KeyCombo),
+    /// 展开占位符后输入文本
+    TypeText(Snippet),
+    /// 以指定的参数、工作目录和环境变量启动程序
+    Exec(ExecSpec),
+    /// 最大化、吸附、移动前台窗口等
+    Window(WindowCommand),
+    /// 按顺序执行多个动作, 相邻动作之间等待 delay
+    Sequence {
+        steps: Vec<Action>,
+        delay: Duration,
     },
 }
 
@@ -0,0 +1,250 @@
+use std::time::{Duration, SystemTime};
+
+use rdev::Key;
+
+/// 触发键单击/按住判定的时间参数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TapHoldTiming {
+    /// 按住超过该时长后松开不再执行单击动作, None 表示不限制
+    pub tap_timeout: Option<Duration>,
+    /// 触发键按下后经过该时长, 其他按键才直接按层的绑定处理
+    pub hold_delay: Duration,
+    /// 在 hold_delay 内按下的按键若在触发键松开前松开, 仍按层的绑定处理
+    pub permissive_hold: bool,
+}
+
+/// 在层已激活时按下绑定按键的判定结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyDecision {
+    /// 按层的绑定处理
+    Hold,
+    /// 暂存按键, 等它或触发键先松开后再判定
+    Wait,
+    /// 暂存的按键和当前按键都按层的绑定处理, 先处理暂存的按键
+    HoldWaiting(Key),
+    /// 视为连续打字, 触发键按单击处理
+    Tap,
+}
+
+/// 触发键松开时的判定结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Release {
+    /// 是否执行单击动作
+    pub tap: bool,
+    /// 暂存的按键, 需要在触发键之后补发
+    pub waiting: Option<Key>,
+}
+
+/// 单次按住触发键期间的单击/按住状态, 时间戳由调用方传入 (键盘事件的时间)
+#[derive(Debug, Clone)]
+pub struct TapHold {
+    timing: TapHoldTiming,
+    /// 触发键单击时是否输出字符, CapsLock 这类不输出字符的触发键在延迟内不会按连续打字处理
+    types_on_tap: bool,
+    pressed_at: SystemTime,
+    held: bool,
+    waiting: Option<Key>,
+}
+
+impl TapHold {
+    pub fn new(timing: TapHoldTiming, types_on_tap: bool, pressed_at: SystemTime) -> Self {
+        Self {
+            timing,
+            types_on_tap,
+            pressed_at,
+            held: false,
+            waiting: None,
+        }
+    }
+
+    pub fn waiting(&self) -> Option<Key> {
+        self.waiting
+    }
+
+    fn elapsed(&self, now: SystemTime) -> Duration {
+        now.duration_since(self.pressed_at).unwrap_or_default()
+    }
+
+    pub fn key_pressed(&mut self, key: Key, now: SystemTime) -> KeyDecision {
+        if self.waiting == Some(key) {
+            return KeyDecision::Wait;
+        }
+        if self.held || self.elapsed(now) >= self.timing.hold_delay {
+            self.held = true;
+            return KeyDecision::Hold;
+        }
+        // 不输出字符的触发键无从连续打字, 延迟内的第一个按键总是暂存等待组合
+        if (self.timing.permissive_hold || !self.types_on_tap) && self.waiting.is_none() {
+            self.waiting = Some(key);
+            return KeyDecision::Wait;
+        }
+        // 暂存期间又按下第二个键: CapsLock 这类触发键按连续组合处理, 不能切换输入法
+        if !self.types_on_tap
+            && let Some(waiting) = self.waiting.take()
+        {
+            self.held = true;
+            return KeyDecision::HoldWaiting(waiting);
+        }
+        KeyDecision::Tap
+    }
+
+    /// 暂存的按键在触发键之前松开时返回 true, 此时层被激活
+    pub fn key_released(&mut self, key: Key) -> bool {
+        if self.waiting != Some(key) {
+            return false;
+        }
+        self.waiting = None;
+        self.held = true;
+        true
+    }
+
+    pub fn released(self, now: SystemTime) -> Release {
+        let tap = self
+            .timing
+            .tap_timeout
+            .is_none_or(|timeout| self.elapsed(now) < timeout);
+        Release {
+            tap,
+            waiting: self.waiting,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use rdev::Key;
+
+    use super::{KeyDecision, Release, TapHold, TapHoldTiming};
+
+    fn at(ms: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_millis(ms)
+    }
+
+    fn timing(tap_timeout: Option<u64>, hold_delay: u64, permissive_hold: bool) -> TapHoldTiming {
+        TapHoldTiming {
+            tap_timeout: tap_timeout.map(Duration::from_millis),
+            hold_delay: Duration::from_millis(hold_delay),
+            permissive_hold,
+        }
+    }
+
+    #[test]
+    fn default_timing_keeps_immediate_hold() {
+        let mut tap_hold = TapHold::new(TapHoldTiming::default(), true, at(0));
+        assert_eq!(tap_hold.key_pressed(Key::KeyH, at(1)), KeyDecision::Hold);
+
+        let tap_hold = TapHold::new(TapHoldTiming::default(), true, at(0));
+        let release = tap_hold.released(at(5_000));
+        assert_eq!(
+            release,
+            Release {
+                tap: true,
+                waiting: None
+            }
+        );
+    }
+
+    #[test]
+    fn tap_timeout_cancels_long_press() {
+        let tap_hold = TapHold::new(timing(Some(200), 0, false), true, at(1_000));
+        assert!(tap_hold.released(at(1_199)).tap);
+
+        let tap_hold = TapHold::new(timing(Some(200), 0, false), true, at(1_000));
+        assert!(!tap_hold.released(at(1_200)).tap);
+    }
+
+    #[test]
+    fn hold_delay_treats_early_keys_as_typing() {
+        let mut tap_hold = TapHold::new(timing(None, 150, false), true, at(0));
+        assert_eq!(tap_hold.key_pressed(Key::KeyJ, at(100)), KeyDecision::Tap);
+
+        let mut tap_hold = TapHold::new(timing(None, 150, false), true, at(0));
+        assert_eq!(tap_hold.key_pressed(Key::KeyJ, at(150)), KeyDecision::Hold);
+        // 层激活后不再受延迟影响
+        assert_eq!(tap_hold.key_pressed(Key::KeyK, at(151)), KeyDecision::Hold);
+    }
+
+    #[test]
+    fn permissive_hold_waits_for_release_order() {
+        // 按键先于触发键松开: 组合键
+        let mut tap_hold = TapHold::new(timing(None, 150, true), true, at(0));
+        assert_eq!(tap_hold.key_pressed(Key::KeyJ, at(50)), KeyDecision::Wait);
+        assert_eq!(tap_hold.key_pressed(Key::KeyJ, at(80)), KeyDecision::Wait);
+        assert!(!tap_hold.key_released(Key::KeyK));
+        assert!(tap_hold.key_released(Key::KeyJ));
+        assert_eq!(tap_hold.key_pressed(Key::KeyK, at(100)), KeyDecision::Hold);
+
+        // 触发键先松开: 连续打字, 暂存的按键需要补发
+        let mut tap_hold = TapHold::new(timing(None, 150, true), true, at(0));
+        assert_eq!(tap_hold.key_pressed(Key::KeyJ, at(50)), KeyDecision::Wait);
+        assert_eq!(tap_hold.waiting(), Some(Key::KeyJ));
+        assert_eq!(
+            tap_hold.released(at(90)),
+            Release {
+                tap: true,
+                waiting: Some(Key::KeyJ)
+            }
+        );
+
+        // 暂存期间又按下第二个键: 连续打字
+        let mut tap_hold = TapHold::new(timing(None, 150, true), true, at(0));
+        assert_eq!(tap_hold.key_pressed(Key::KeyJ, at(50)), KeyDecision::Wait);
+        assert_eq!(tap_hold.key_pressed(Key::KeyK, at(60)), KeyDecision::Tap);
+    }
+
+    #[test]
+    fn hold_delay_waits_for_chord_on_silent_trigger() {
+        // CapsLock 在延迟内快速组合: 按键先松开, 按组合键处理
+        let mut tap_hold = TapHold::new(timing(None, 150, false), false, at(0));
+        assert_eq!(tap_hold.key_pressed(Key::KeyH, at(30)), KeyDecision::Wait);
+        assert!(tap_hold.key_released(Key::KeyH));
+        assert_eq!(tap_hold.key_pressed(Key::KeyL, at(60)), KeyDecision::Hold);
+
+        // 触发键先松开: 单击后补发暂存的按键
+        let mut tap_hold = TapHold::new(timing(None, 150, false), false, at(0));
+        assert_eq!(tap_hold.key_pressed(Key::KeyH, at(30)), KeyDecision::Wait);
+        assert_eq!(
+            tap_hold.released(at(60)),
+            Release {
+                tap: true,
+                waiting: Some(Key::KeyH)
+            }
+        );
+    }
+
+    #[test]
+    fn second_key_settles_chord_on_silent_trigger() {
+        // CapsLock+H,J 快速连按: 两个键都按组合键处理, 不按单击切换输入法
+        let mut tap_hold = TapHold::new(timing(None, 150, false), false, at(0));
+        assert_eq!(tap_hold.key_pressed(Key::KeyH, at(30)), KeyDecision::Wait);
+        assert_eq!(
+            tap_hold.key_pressed(Key::KeyJ, at(50)),
+            KeyDecision::HoldWaiting(Key::KeyH)
+        );
+        assert_eq!(tap_hold.waiting(), None);
+        // 暂存的按键已经处理过, 松开时不再重复处理
+        assert!(!tap_hold.key_released(Key::KeyH));
+        assert_eq!(tap_hold.key_pressed(Key::KeyL, at(70)), KeyDecision::Hold);
+        assert_eq!(
+            tap_hold.released(at(90)),
+            Release {
+                tap: true,
+                waiting: None
+            }
+        );
+    }
+
+    #[test]
+    fn waiting_key_resolves_on_release_after_delay() {
+        // 延迟过后按下的其他按键直接处理, 暂存的按键仍在自己松开时处理一次
+        let mut tap_hold = TapHold::new(timing(None, 150, false), false, at(0));
+        assert_eq!(tap_hold.key_pressed(Key::KeyH, at(30)), KeyDecision::Wait);
+        assert_eq!(tap_hold.key_pressed(Key::KeyH, at(60)), KeyDecision::Wait);
+        assert_eq!(tap_hold.key_pressed(Key::KeyJ, at(200)), KeyDecision::Hold);
+        assert!(tap_hold.key_released(Key::KeyH));
+        assert!(!tap_hold.key_released(Key::KeyH));
+        assert_eq!(tap_hold.released(at(300)).waiting, None);
+    }
+}
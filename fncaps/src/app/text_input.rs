@@ -0,0 +1,119 @@
+use std::mem::size_of;
+
+use rdev::Key;
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    GetAsyncKeyState, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBD_EVENT_FLAGS, KEYBDINPUT,
+    KEYEVENTF_KEYUP, KEYEVENTF_UNICODE, SendInput, VIRTUAL_KEY, VK_RETURN, VK_TAB,
+};
+
+use super::state::STATE;
+use super::worker::MODIFIER_KEYS;
+
+/// 以 Unicode 按键事件输入文本, 不经过键盘布局和输入法, 中文等字符可以直接输入
+pub fn type_text(text: &str) {
+    // 输入文本前需要临时抬起按下的修饰键, 否则字符会变成快捷键
+    let held: Vec<(VIRTUAL_KEY, Key)> = MODIFIER_KEYS
+        .into_iter()
+        .filter(|(vk, _)| unsafe { GetAsyncKeyState(vk.0 as i32) } < 0)
+        .collect();
+
+    let mut inputs = Vec::with_capacity(text.len() * 2 + held.len() * 2);
+    // 实际按键会经过键盘钩子, 需要记录在 State::injected 中才会被直接放行
+    let mut injected = Vec::new();
+    for &(vk, key) in &held {
+        push_key(&mut inputs, &mut injected, vk, key, false);
+    }
+    for c in text.chars() {
+        match c {
+            '\r' => {}
+            // 换行和制表符按实际按键发送, 部分程序不处理 Unicode 形式的控制字符
+            '\n' => {
+                push_key(&mut inputs, &mut injected, VK_RETURN, Key::Return, true);
+                push_key(&mut inputs, &mut injected, VK_RETURN, Key::Return, false);
+            }
+            '\t' => {
+                push_key(&mut inputs, &mut injected, VK_TAB, Key::Tab, true);
+                push_key(&mut inputs, &mut injected, VK_TAB, Key::Tab, false);
+            }
+            c => {
+                let mut units = [0u16; 2];
+                for &unit in c.encode_utf16(&mut units).iter() {
+                    inputs.push(unicode_input(unit, KEYEVENTF_UNICODE));
+                    inputs.push(unicode_input(unit, KEYEVENTF_UNICODE | KEYEVENTF_KEYUP));
+                }
+            }
+        }
+    }
+    for &(vk, key) in &held {
+        push_key(&mut inputs, &mut injected, vk, key, true);
+    }
+
+    if !injected.is_empty() {
+        let Ok(mut state) = STATE.lock() else {
+            return;
+        };
+        state
+            .injected
+            .extend(injected.iter().map(|&(_, event)| event));
+    }
+
+    let sent = unsafe { SendInput(&inputs, size_of::<INPUT>() as i32) };
+    if sent as usize == inputs.len() {
+        tracing::debug!(target: "fncaps::text_input", events = sent, lifted = held.len(), "text typed");
+    } else {
+        // 没有发出的事件不会回到钩子, 留在 State::injected 中会让之后真实的同名按键被直接放行
+        if let Ok(mut state) = STATE.lock() {
+            for &(_, event) in injected.iter().filter(|(index, _)| *index >= sent as usize) {
+                if let Some(position) = state.injected.iter().rposition(|&e| e == event) {
+                    state.injected.remove(position);
+                }
+            }
+        }
+        tracing::warn!(
+            target: "fncaps::text_input",
+            sent,
+            expected = inputs.len(),
+            "SendInput was blocked, text typed partially"
+        );
+    }
+}
+
+/// 追加一个实际按键事件, 并记录它在 inputs 中的下标和对应的 (key, 是否按下)
+fn push_key(
+    inputs: &mut Vec<INPUT>,
+    injected: &mut Vec<(usize, (Key, bool))>,
+    vk: VIRTUAL_KEY,
+    key: Key,
+    is_pressing: bool,
+) {
+    let flags = if is_pressing {
+        KEYBD_EVENT_FLAGS(0)
+    } else {
+        KEYEVENTF_KEYUP
+    };
+    injected.push((inputs.len(), (key, is_pressing)));
+    inputs.push(key_input(vk, flags));
+}
+
+fn key_input(vk: VIRTUAL_KEY, flags: KEYBD_EVENT_FLAGS) -> INPUT {
+    keyboard_input(vk, 0, flags)
+}
+
+fn unicode_input(unit: u16, flags: KEYBD_EVENT_FLAGS) -> INPUT {
+    keyboard_input(VIRTUAL_KEY(0), unit, flags)
+}
+
+fn keyboard_input(vk: VIRTUAL_KEY, scan: u16, flags: KEYBD_EVENT_FLAGS) -> INPUT {
+    INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: vk,
+                wScan: scan,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    }
+}
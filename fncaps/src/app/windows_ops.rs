@@ -1,22 +1,39 @@
 use std::f64::consts::PI;
-use std::ffi::OsString;
+use std::ffi::{OsString, c_void};
 use std::os::windows::ffi::OsStringExt;
 
-use windows::Win32::Foundation::{BOOL, HWND, LPARAM, POINT, RECT};
+use windows::Win32::Foundation::{BOOL, HWND, LPARAM, POINT, RECT, WPARAM};
+use windows::Win32::Graphics::Dwm::{DWMWA_EXTENDED_FRAME_BOUNDS, DwmGetWindowAttribute};
 use windows::Win32::Graphics::Gdi::{
     EnumDisplayMonitors, GetMonitorInfoW, HDC, HMONITOR, MONITORINFO,
 };
 use windows::Win32::UI::WindowsAndMessaging::{
-    EnumWindows, GA_ROOT, GetAncestor, GetForegroundWindow, GetWindowRect, GetWindowTextLengthW,
-    GetWindowTextW, GetWindowThreadProcessId, IsWindowVisible, IsZoomed, SW_SHOW, SetCursorPos,
-    SetForegroundWindow, ShowWindow, WindowFromPoint,
+    EnumWindows, GA_ROOT, GWL_EXSTYLE, GetAncestor, GetClassNameW, GetForegroundWindow,
+    GetWindowLongW, GetWindowRect, GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId,
+    HWND_NOTOPMOST, HWND_TOPMOST, IsIconic, IsWindowVisible, IsZoomed, PostMessageW,
+    SHOW_WINDOW_CMD, SW_MAXIMIZE, SW_MINIMIZE, SW_RESTORE, SW_SHOW, SWP_NOACTIVATE, SWP_NOMOVE,
+    SWP_NOSIZE, SWP_NOZORDER, SetCursorPos, SetForegroundWindow, SetWindowPos, ShowWindow,
+    WM_CLOSE, WS_EX_TOPMOST, WindowFromPoint,
 };
 
-use super::state::Direction;
+use super::layout::{
+    Monitor, MonitorTarget, Rect, Snap, monitor_of, move_between, snap_rect, sort_monitors,
+    target_monitor, with_frame,
+};
+use super::state::{Direction, WindowCommand};
 
 const MIN_VALID_WINDOW_WIDTH: i32 = 30;
 const MIN_VALID_WINDOW_HEIGHT: i32 = 30;
 
+/// 桌面和任务栏也可能成为前台窗口, 不对它们执行窗口管理操作,
+/// 例如对桌面发送 WM_CLOSE 会弹出关机对话框
+const SHELL_WINDOW_CLASSES: &[&str] = &[
+    "Progman",
+    "WorkerW",
+    "Shell_TrayWnd",
+    "Shell_SecondaryTrayWnd",
+];
+
 #[derive(Debug, Clone, Copy)]
 struct ScreenBounds {
     start_x: i32,
@@ -101,27 +118,19 @@ pub fn switch_to(direction: Direction) {
     }
 }
 
-fn update_screen_size() -> Option<ScreenBounds> {
-    #[derive(Clone, Copy)]
-    struct MonitorCollect {
-        start_x: i32,
-        start_y: i32,
-        end_x: i32,
-        end_y: i32,
-        initialized: bool,
-    }
-
+/// 枚举所有显示器, 按从左到右、从上到下排序
+fn enumerate_monitors() -> Vec<Monitor> {
     unsafe extern "system" fn monitor_enum_proc(
         monitor: HMONITOR,
         _hdc: HDC,
         _rect: *mut RECT,
         lparam: LPARAM,
     ) -> BOOL {
-        let collect = lparam.0 as *mut MonitorCollect;
-        if collect.is_null() {
+        let monitors = lparam.0 as *mut Vec<Monitor>;
+        if monitors.is_null() {
             return BOOL(0);
         }
-        let collect = unsafe { &mut *collect };
+        let monitors = unsafe { &mut *monitors };
 
         let mut info = MONITORINFO {
             cbSize: std::mem::size_of::<MONITORINFO>() as u32,
@@ -129,60 +138,63 @@ fn update_screen_size() -> Option<ScreenBounds> {
         };
 
         if unsafe { GetMonitorInfoW(monitor, &mut info as *mut MONITORINFO) }.as_bool() {
-            let rect = info.rcMonitor;
-            if !collect.initialized {
-                collect.start_x = rect.left;
-                collect.start_y = rect.top;
-                collect.end_x = rect.right;
-                collect.end_y = rect.bottom;
-                collect.initialized = true;
-            } else {
-                collect.start_x = collect.start_x.min(rect.left);
-                collect.start_y = collect.start_y.min(rect.top);
-                collect.end_x = collect.end_x.max(rect.right);
-                collect.end_y = collect.end_y.max(rect.bottom);
-            }
+            monitors.push(Monitor {
+                bounds: to_layout_rect(info.rcMonitor),
+                work: to_layout_rect(info.rcWork),
+            });
         }
 
         BOOL(1)
     }
 
-    let mut collect = MonitorCollect {
-        start_x: 0,
-        start_y: 0,
-        end_x: 0,
-        end_y: 0,
-        initialized: false,
-    };
-
+    let mut monitors = Vec::<Monitor>::new();
     unsafe {
         let _ = EnumDisplayMonitors(
             HDC::default(),
             None,
             Some(monitor_enum_proc),
-            LPARAM((&mut collect as *mut MonitorCollect) as isize),
+            LPARAM((&mut monitors as *mut Vec<Monitor>) as isize),
         );
     }
 
-    if !collect.initialized {
-        return None;
-    }
+    sort_monitors(&mut monitors);
+    monitors
+}
 
-    let total_width = collect.end_x - collect.start_x;
-    let total_height = collect.end_y - collect.start_y;
+fn update_screen_size() -> Option<ScreenBounds> {
+    let monitors = enumerate_monitors();
+    let first = monitors.first()?.bounds;
+    let (start_x, start_y, end_x, end_y) = monitors.iter().fold(
+        (first.left, first.top, first.right, first.bottom),
+        |(start_x, start_y, end_x, end_y), monitor| {
+            (
+                start_x.min(monitor.bounds.left),
+                start_y.min(monitor.bounds.top),
+                end_x.max(monitor.bounds.right),
+                end_y.max(monitor.bounds.bottom),
+            )
+        },
+    );
+
+    let total_width = end_x - start_x;
+    let total_height = end_y - start_y;
     let diagonal_square = (total_width as f64).powi(2) + (total_height as f64).powi(2);
 
     Some(ScreenBounds {
-        start_x: collect.start_x,
-        start_y: collect.start_y,
-        end_x: collect.end_x,
-        end_y: collect.end_y,
+        start_x,
+        start_y,
+        end_x,
+        end_y,
         total_width,
         total_height,
         diagonal_square,
     })
 }
 
+fn to_layout_rect(rect: RECT) -> Rect {
+    Rect::new(rect.left, rect.top, rect.right, rect.bottom)
+}
+
 fn get_window_rect(hwnd: HWND) -> Option<RECT> {
     let mut rect = RECT::default();
     let ok = unsafe { GetWindowRect(hwnd, &mut rect) };
@@ -376,7 +388,7 @@ pub fn switch_to_window(title: &str) {
 }
 
 /// 切换到指定窗口，或如果窗口不存在则打开程序
-pub fn switch_to_window_or_open(window_title: &str, program: &str) {
+pub fn switch_to_window_or_open(window_title: &str, program: &str, args: &[String]) {
     tracing::info!(
         target: "fncaps::windows",
         window_title,
@@ -386,7 +398,7 @@ pub fn switch_to_window_or_open(window_title: &str, program: &str) {
 
     let Some(bounds) = update_screen_size() else {
         tracing::warn!(target: "fncaps::windows", "cannot get monitor bounds, fallback to opening program");
-        super::launch::open_program(program);
+        super::launch::open_program(program, args);
         return;
     };
 
@@ -422,5 +434,180 @@ pub fn switch_to_window_or_open(window_title: &str, program: &str) {
     }
 
     tracing::warn!(target: "fncaps::windows", window_title, "window not found, launching program");
-    super::launch::open_program(program);
+    super::launch::open_program(program, args);
+}
+
+/// 对前台窗口执行窗口管理操作
+pub fn manage_foreground(command: WindowCommand) {
+    let Some(hwnd) = manageable_foreground_window() else {
+        tracing::debug!(target: "fncaps::windows", ?command, "no manageable foreground window");
+        return;
+    };
+
+    match command {
+        WindowCommand::Maximize => show_window(hwnd, SW_MAXIMIZE),
+        WindowCommand::Minimize => show_window(hwnd, SW_MINIMIZE),
+        WindowCommand::Restore => show_window(hwnd, SW_RESTORE),
+        WindowCommand::Close => {
+            if let Err(e) = unsafe { PostMessageW(hwnd, WM_CLOSE, WPARAM(0), LPARAM(0)) } {
+                tracing::warn!(target: "fncaps::windows", error = %e, "failed to close window");
+            }
+        }
+        WindowCommand::ToggleTopmost => toggle_topmost(hwnd),
+        WindowCommand::Snap(snap) => snap_window(hwnd, snap),
+        WindowCommand::MoveToMonitor(target) => move_to_monitor(hwnd, target),
+    }
+}
+
+fn manageable_foreground_window() -> Option<HWND> {
+    let hwnd = unsafe { GetForegroundWindow() };
+    if hwnd.0.is_null() {
+        return None;
+    }
+    let class = get_class_name(hwnd)?;
+    if SHELL_WINDOW_CLASSES.contains(&class.as_str()) {
+        return None;
+    }
+    Some(hwnd)
+}
+
+fn get_class_name(hwnd: HWND) -> Option<String> {
+    let mut buffer = [0u16; 256];
+    let len = unsafe { GetClassNameW(hwnd, &mut buffer) };
+    if len <= 0 {
+        return None;
+    }
+    Some(String::from_utf16_lossy(&buffer[..len as usize]))
+}
+
+fn show_window(hwnd: HWND, command: SHOW_WINDOW_CMD) {
+    unsafe {
+        let _ = ShowWindow(hwnd, command);
+    }
+}
+
+fn toggle_topmost(hwnd: HWND) {
+    let ex_style = unsafe { GetWindowLongW(hwnd, GWL_EXSTYLE) } as u32;
+    let topmost = ex_style & WS_EX_TOPMOST.0 != 0;
+    let insert_after = if topmost {
+        HWND_NOTOPMOST
+    } else {
+        HWND_TOPMOST
+    };
+    let result = unsafe {
+        SetWindowPos(
+            hwnd,
+            insert_after,
+            0,
+            0,
+            0,
+            0,
+            SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+        )
+    };
+    match result {
+        Ok(()) => {
+            tracing::debug!(target: "fncaps::windows", topmost = !topmost, "window topmost toggled")
+        }
+        Err(e) => tracing::warn!(target: "fncaps::windows", error = %e, "failed to toggle topmost"),
+    }
+}
+
+/// 窗口的可见区域, Windows 10 起 GetWindowRect 还包含不可见的缩放边框
+fn get_frame_rect(hwnd: HWND) -> Option<RECT> {
+    let mut rect = RECT::default();
+    let result = unsafe {
+        DwmGetWindowAttribute(
+            hwnd,
+            DWMWA_EXTENDED_FRAME_BOUNDS,
+            &mut rect as *mut RECT as *mut c_void,
+            std::mem::size_of::<RECT>() as u32,
+        )
+    };
+    match result {
+        Ok(()) => Some(rect),
+        Err(_) => get_window_rect(hwnd),
+    }
+}
+
+/// 把窗口的可见区域移到 target, 最大化或最小化的窗口需要先还原
+fn place_window(hwnd: HWND, target: Rect) {
+    let (Some(window), Some(frame)) = (get_window_rect(hwnd), get_frame_rect(hwnd)) else {
+        return;
+    };
+    let rect = with_frame(target, to_layout_rect(window), to_layout_rect(frame));
+    let result = unsafe {
+        SetWindowPos(
+            hwnd,
+            HWND::default(),
+            rect.left,
+            rect.top,
+            rect.width(),
+            rect.height(),
+            SWP_NOZORDER | SWP_NOACTIVATE,
+        )
+    };
+    match result {
+        Ok(()) => tracing::debug!(target: "fncaps::windows", ?target, "window moved"),
+        // 普通权限无法移动以管理员身份运行的窗口
+        Err(e) => tracing::warn!(target: "fncaps::windows", error = %e, "failed to move window"),
+    }
+}
+
+fn restore_if_needed(hwnd: HWND) -> bool {
+    let maximized = is_window_maximized(hwnd);
+    if maximized || unsafe { IsIconic(hwnd).as_bool() } {
+        show_window(hwnd, SW_RESTORE);
+    }
+    maximized
+}
+
+fn snap_window(hwnd: HWND, snap: Snap) {
+    let monitors = enumerate_monitors();
+    let Some(frame) = get_frame_rect(hwnd).map(to_layout_rect) else {
+        return;
+    };
+    // 按还原前的位置确定显示器, 还原后的位置可能在其他显示器上
+    let Some(index) = monitor_of(&monitors, frame) else {
+        tracing::warn!(target: "fncaps::windows", "cannot get monitor bounds");
+        return;
+    };
+
+    restore_if_needed(hwnd);
+    let Some(frame) = get_frame_rect(hwnd).map(to_layout_rect) else {
+        return;
+    };
+    tracing::info!(target: "fncaps::windows", snap = %snap, monitor = index + 1, "snapping window");
+    place_window(hwnd, snap_rect(snap, monitors[index].work, frame));
+}
+
+fn move_to_monitor(hwnd: HWND, target: MonitorTarget) {
+    let monitors = enumerate_monitors();
+    let Some(frame) = get_frame_rect(hwnd).map(to_layout_rect) else {
+        return;
+    };
+    let Some(current) = monitor_of(&monitors, frame) else {
+        tracing::warn!(target: "fncaps::windows", "cannot get monitor bounds");
+        return;
+    };
+    let Some(next) = target_monitor(monitors.len(), current, target) else {
+        tracing::warn!(target: "fncaps::windows", %target, count = monitors.len(), "target monitor not found");
+        return;
+    };
+    if next == current {
+        return;
+    }
+
+    let maximized = restore_if_needed(hwnd);
+    let Some(frame) = get_frame_rect(hwnd).map(to_layout_rect) else {
+        return;
+    };
+    tracing::info!(target: "fncaps::windows", from = current + 1, to = next + 1, "moving window to monitor");
+    place_window(
+        hwnd,
+        move_between(frame, monitors[current].work, monitors[next].work),
+    );
+    if maximized {
+        show_window(hwnd, SW_MAXIMIZE);
+    }
 }
@@ -0,0 +1,190 @@
+use std::sync::OnceLock;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+use rdev::Key;
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    GetAsyncKeyState, KEYEVENTF_KEYUP, MOUSEEVENTF_WHEEL, VIRTUAL_KEY, VK_LCONTROL, VK_LMENU,
+    VK_LSHIFT, VK_LWIN, VK_RCONTROL, VK_RMENU, VK_RSHIFT, VK_RWIN, keybd_event, mouse_event,
+};
+
+use super::combo::KeyCombo;
+use super::hotkey::replay_keys;
+use super::ime;
+use super::launch;
+use super::snippet::SystemContext;
+use super::state::{Action, ModifierState, RIGHT_WIN, STATE};
+use super::text_input;
+use super::windows_ops;
+
+/// 修饰键的虚拟键码与 rdev 按键的对应关系
+pub const MODIFIER_KEYS: [(VIRTUAL_KEY, Key); 8] = [
+    (VK_LSHIFT, Key::ShiftLeft),
+    (VK_RSHIFT, Key::ShiftRight),
+    (VK_LCONTROL, Key::ControlLeft),
+    (VK_RCONTROL, Key::ControlRight),
+    (VK_LMENU, Key::Alt),
+    (VK_RMENU, Key::AltGr),
+    (VK_LWIN, Key::MetaLeft),
+    (VK_RWIN, RIGHT_WIN),
+];
+
+/// 键盘钩子只负责判定按键, 动作交给工作线程按顺序执行, 启动程序等耗时操作不会卡住全局输入
+static SENDER: OnceLock<Sender<(Action, Key)>> = OnceLock::new();
+
+pub fn start() -> Result<(), String> {
+    let (sender, receiver) = mpsc::channel::<(Action, Key)>();
+    thread::Builder::new()
+        .name("fncaps-actions".to_string())
+        .spawn(move || {
+            for (action, key) in receiver {
+                if waits_for_exit(&action) {
+                    execute_detached(action, key);
+                } else {
+                    execute_action(action, key);
+                }
+            }
+        })
+        .map_err(|e| format!("failed to spawn action worker: {e}"))?;
+    let _ = SENDER.set(sender);
+    tracing::info!(target: "fncaps::worker", "action worker started");
+    Ok(())
+}
+
+pub fn submit(action: Action, key: Key) {
+    let Some(sender) = SENDER.get() else {
+        tracing::warn!(target: "fncaps::worker", ?action, "action worker not started, drop action");
+        return;
+    };
+    if let Err(mpsc::SendError((action, _))) = sender.send((action, key)) {
+        tracing::error!(target: "fncaps::worker", ?action, "action worker stopped, drop action");
+    }
+}
+
+/// 是否包含 wait = true 的 exec, 等待程序退出期间不能占用工作线程
+fn waits_for_exit(action: &Action) -> bool {
+    match action {
+        Action::Exec(spec) => spec.wait,
+        Action::Sequence { steps, .. } => steps.iter().any(waits_for_exit),
+        _ => false,
+    }
+}
+
+/// 在单独的线程中执行动作, 其后提交的动作不必等它完成
+fn execute_detached(action: Action, key: Key) {
+    let spawned = thread::Builder::new()
+        .name("fncaps-exec-wait".to_string())
+        .spawn(move || execute_action(action, key));
+    if let Err(e) = spawned {
+        tracing::error!(target: "fncaps::worker", error = %e, "failed to spawn thread for waiting action");
+    }
+}
+
+fn execute_action(action: Action, key: Key) {
+    match action {
+        Action::None => {}
+        Action::SwitchTo(direction) => {
+            tracing::info!(target: "fncaps::hotkey", ?direction, ?key, "trigger window switch");
+            windows_ops::switch_to(direction)
+        }
+        Action::Scroll(delta) => {
+            tracing::info!(target: "fncaps::hotkey", delta, ?key, "trigger wheel scrolling");
+            scroll_with_lshift_workaround(delta)
+        }
+        Action::SwitchIme => {
+            tracing::info!(target: "fncaps::hotkey", "caps tap detected, switching IME");
+            ime::switch_im()
+        }
+        Action::OpenProgram { program, args } => {
+            tracing::info!(target: "fncaps::hotkey", ?key, program = %program, ?args, "trigger custom program launch");
+            launch::open_program(&program, &args)
+        }
+        Action::SwitchWindow { title } => {
+            tracing::info!(target: "fncaps::hotkey", ?key, window_title = %title, "trigger window switch to specific");
+            windows_ops::switch_to_window(&title)
+        }
+        Action::SwitchOrOpen {
+            window_title,
+            program,
+            args,
+        } => {
+            tracing::info!(
+                target: "fncaps::hotkey",
+                ?key,
+                window_title = %window_title,
+                program = %program,
+                "trigger switch-or-open"
+            );
+            windows_ops::switch_to_window_or_open(&window_title, &program, &args)
+        }
+        Action::SendKeys(combo) => {
+            tracing::info!(target: "fncaps::hotkey", ?key, combo = %combo, "trigger key combo");
+            send_keys(&combo)
+        }
+        Action::TypeText(snippet) => {
+            // 只记录长度, 展开后的文本可能包含剪贴板内容
+            let text = snippet.expand(&SystemContext);
+            tracing::info!(target: "fncaps::hotkey", ?key, chars = text.chars().count(), "trigger text typing");
+            text_input::type_text(&text)
+        }
+        Action::Exec(spec) => {
+            tracing::info!(target: "fncaps::hotkey", ?key, program = %spec.program, wait = spec.wait, "trigger exec");
+            launch::exec(&spec)
+        }
+        Action::Window(command) => {
+            tracing::info!(target: "fncaps::hotkey", ?key, ?command, "trigger window management");
+            windows_ops::manage_foreground(command)
+        }
+        Action::Sequence { steps, delay } => {
+            tracing::info!(target: "fncaps::hotkey", ?key, steps = steps.len(), ?delay, "trigger action sequence");
+            for (index, step) in steps.into_iter().enumerate() {
+                if index > 0 && !delay.is_zero() {
+                    thread::sleep(delay);
+                }
+                execute_action(step, key);
+            }
+        }
+    }
+}
+
+/// 按执行时的修饰键状态生成模拟事件, 先记录到 State::injected 再发出, 钩子会直接放行
+fn send_keys(combo: &KeyCombo) {
+    // 动作排队期间修饰键可能已经松开, 按执行时实际按下的修饰键生成事件, 避免把已松开的键重新按下
+    let held = held_modifiers();
+    let events = {
+        let mut state = match STATE.lock() {
+            Ok(s) => s,
+            Err(_) => return,
+        };
+        let events = combo.plan(held);
+        state.injected.extend(events.iter().copied());
+        events
+    };
+    replay_keys(&events);
+}
+
+/// 用 GetAsyncKeyState 读取当前实际按下的修饰键
+fn held_modifiers() -> ModifierState {
+    let mut held = ModifierState::default();
+    for (vk, key) in MODIFIER_KEYS {
+        if unsafe { GetAsyncKeyState(vk.0 as i32) } < 0 {
+            held.update(key, true);
+        }
+    }
+    held
+}
+
+/// 工作线程没有输入队列, 用 GetAsyncKeyState 读取左 Shift 的实际状态
+fn scroll_with_lshift_workaround(delta: i32) {
+    let shift_down = unsafe { GetAsyncKeyState(VK_LSHIFT.0 as i32) } < 0;
+    unsafe {
+        if shift_down {
+            keybd_event(VK_LSHIFT.0 as u8, 0, KEYEVENTF_KEYUP, 0);
+        }
+        mouse_event(MOUSEEVENTF_WHEEL, 0, 0, delta * 120, 0);
+        if shift_down {
+            keybd_event(VK_LSHIFT.0 as u8, 0, Default::default(), 0);
+        }
+    }
+    tracing::debug!(target: "fncaps::hotkey", delta, shift_down, "scroll wheel sent");
+}
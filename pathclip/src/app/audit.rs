@@ -0,0 +1,285 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, FixedOffset, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum Trigger {
+    Auto,
+    Hotkey { id: u32 },
+    Control,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Outcome {
+    Converted,
+    Unchanged,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<FixedOffset>,
+    pub trigger: Trigger,
+    pub profile: String,
+    pub path_count: usize,
+    pub duration_ms: u64,
+    pub outcome: Outcome,
+    /// 只包含最外层的错误信息, 不含 exec 的 stderr 或脚本中的值
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 完整的错误链, 可能包含剪贴板内容, 只在 log_contents 时记录
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_detail: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn new(trigger: Trigger, profile: &str, started: Instant, outcome: Outcome) -> Self {
+        Self {
+            timestamp: Local::now().fixed_offset(),
+            trigger,
+            profile: profile.to_string(),
+            path_count: 0,
+            duration_ms: started.elapsed().as_millis() as u64,
+            outcome,
+            error: None,
+            error_detail: None,
+            input: None,
+            output: None,
+        }
+    }
+
+    pub fn with_paths(mut self, path_count: usize) -> Self {
+        self.path_count = path_count;
+        self
+    }
+
+    pub fn with_error(mut self, error: &anyhow::Error) -> Self {
+        self.error = Some(error.to_string());
+        self.error_detail = Some(format!("{error:#}"));
+        self
+    }
+
+    pub fn with_contents(mut self, input: &str, output: Option<&str>) -> Self {
+        self.input = Some(input.to_string());
+        self.output = output.map(str::to_string);
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+    log_contents: bool,
+}
+
+impl AuditLog {
+    pub fn new(path: PathBuf, log_contents: bool) -> Self {
+        Self { path, log_contents }
+    }
+
+    pub fn append(&self, mut entry: AuditEntry) -> Result<()> {
+        if !self.log_contents {
+            entry.error_detail = None;
+            entry.input = None;
+            entry.output = None;
+        }
+
+        if let Some(parent) = self.path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create directory: {}", parent.display()))?;
+        }
+        let mut line = serde_json::to_string(&entry).context("failed to encode audit entry")?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .with_context(|| format!("failed to append audit log: {}", self.path.display()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Counts {
+    pub converted: usize,
+    pub unchanged: usize,
+    pub failed: usize,
+    pub paths: usize,
+}
+
+impl Counts {
+    fn add(&mut self, entry: &AuditEntry) {
+        match entry.outcome {
+            Outcome::Converted => {
+                self.converted += 1;
+                self.paths += entry.path_count;
+            }
+            Outcome::Unchanged => self.unchanged += 1,
+            Outcome::Failed => self.failed += 1,
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub profiles: BTreeMap<String, Counts>,
+    pub days: BTreeMap<NaiveDate, Counts>,
+}
+
+impl Stats {
+    pub fn from_reader(reader: impl BufRead) -> Result<Self> {
+        let mut stats = Self::default();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line.context("failed to read audit log")?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: AuditEntry = serde_json::from_str(&line)
+                .with_context(|| format!("line {} is not a valid audit entry", index + 1))?;
+            stats
+                .profiles
+                .entry(entry.profile.clone())
+                .or_default()
+                .add(&entry);
+            stats
+                .days
+                .entry(entry.timestamp.date_naive())
+                .or_default()
+                .add(&entry);
+        }
+        Ok(stats)
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_table(f, "profile", &self.profiles)?;
+        writeln!(f)?;
+        write_table(f, "day", &self.days)
+    }
+}
+
+fn write_table<K: fmt::Display>(
+    f: &mut fmt::Formatter<'_>,
+    title: &str,
+    rows: &BTreeMap<K, Counts>,
+) -> fmt::Result {
+    let keys = rows.keys().map(ToString::to_string).collect::<Vec<_>>();
+    let width = keys
+        .iter()
+        .map(|key| key.chars().count())
+        .chain([title.len()])
+        .max()
+        .unwrap_or_default();
+
+    writeln!(
+        f,
+        "{title:<width$}  {:>9}  {:>9}  {:>6}  {:>6}",
+        "converted", "unchanged", "failed", "paths"
+    )?;
+    for (key, counts) in keys.iter().zip(rows.values()) {
+        writeln!(
+            f,
+            "{key:<width$}  {:>9}  {:>9}  {:>6}  {:>6}",
+            counts.converted, counts.unchanged, counts.failed, counts.paths
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::time::Instant;
+
+    use anyhow::anyhow;
+    use chrono::NaiveDate;
+
+    use super::{AuditEntry, AuditLog, Counts, Outcome, Stats, Trigger};
+
+    #[test]
+    fn contents_are_only_logged_when_enabled() {
+        let dir = std::env::temp_dir().join(format!("pathclip-audit-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let entry = AuditEntry::new(
+            Trigger::Hotkey { id: 7 },
+            "wsl",
+            Instant::now(),
+            Outcome::Converted,
+        )
+        .with_paths(1)
+        .with_contents(r"C:\a.txt", Some("/mnt/c/a.txt"));
+
+        AuditLog::new(dir.join("plain.jsonl"), false)
+            .append(entry.clone())
+            .unwrap();
+        AuditLog::new(dir.join("full.jsonl"), true)
+            .append(entry.clone())
+            .unwrap();
+
+        let plain = fs::read_to_string(dir.join("plain.jsonl")).unwrap();
+        let full = fs::read_to_string(dir.join("full.jsonl")).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(!plain.contains("a.txt"));
+        assert!(plain.contains(r#""trigger":{"type":"hotkey","id":7}"#));
+        assert_eq!(
+            serde_json::from_str::<AuditEntry>(full.trim()).unwrap(),
+            entry
+        );
+    }
+
+    #[test]
+    fn stats_are_grouped_by_profile_and_day() {
+        let log = [
+            r#"{"timestamp":"2026-10-17T23:59:00+08:00","trigger":{"type":"auto"},"profile":"slash","path_count":2,"duration_ms":1,"outcome":"converted"}"#,
+            r#"{"timestamp":"2026-10-18T09:00:00+08:00","trigger":{"type":"hotkey","id":1},"profile":"wsl","path_count":3,"duration_ms":2,"outcome":"converted"}"#,
+            "",
+            r#"{"timestamp":"2026-10-18T09:01:00+08:00","trigger":{"type":"hotkey","id":1},"profile":"wsl","path_count":0,"duration_ms":0,"outcome":"failed","error":"not a path"}"#,
+        ]
+        .join("\n");
+
+        let stats = Stats::from_reader(log.as_bytes()).unwrap();
+
+        assert_eq!(
+            stats.profiles["wsl"],
+            Counts {
+                converted: 1,
+                unchanged: 0,
+                failed: 1,
+                paths: 3,
+            }
+        );
+        assert_eq!(stats.profiles["slash"].paths, 2);
+        let day = |d| NaiveDate::from_ymd_opt(2026, 10, d).unwrap();
+        assert_eq!(stats.days[&day(17)].converted, 1);
+        assert_eq!(stats.days[&day(18)].converted, 1);
+        assert_eq!(stats.days[&day(18)].failed, 1);
+        assert!(stats.to_string().contains("2026-10-18"));
+    }
+
+    #[test]
+    fn invalid_log_line_reports_line_number() {
+        let error = Stats::from_reader("{}\n".as_bytes()).unwrap_err();
+        assert!(error.to_string().contains("line 1"));
+
+        let entry = AuditEntry::new(Trigger::Auto, "slash", Instant::now(), Outcome::Failed)
+            .with_error(&anyhow!("boom"));
+        assert_eq!(entry.error.as_deref(), Some("boom"));
+    }
+}
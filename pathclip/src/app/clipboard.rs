@@ -1,117 +1,95 @@
-use std::thread;
-use std::time::Duration;
-
-use anyhow::{Context, Result, anyhow};
-use clipboard_rs::{Clipboard, ClipboardContext, ClipboardHandler, ClipboardWatcher, ClipboardWatcherContext, ContentFormat, WatcherShutdown};
-use tokio::sync::mpsc::UnboundedSender;
+#[cfg(target_os = "linux")]
+mod command;
+#[cfg(target_os = "windows")]
+mod win32;
+
+use anyhow::Result;
+
+#[cfg(target_os = "linux")]
+pub use command::{ClipboardMonitor, SystemClipboard};
+#[cfg(target_os = "windows")]
+pub use win32::{ClipboardMonitor, SystemClipboard};
+
+const EXCLUDE_FORMATS: [&str; 3] = [
+    "ExcludeClipboardContentFromMonitorProcessing",
+    "Clipboard Viewer Ignore",
+    "x-kde-passwordManagerHint",
+];
+const HISTORY_FORMAT: &str = "CanIncludeInClipboardHistory";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClipboardContents {
+    pub text: String,
+    pub files: Vec<String>,
+    pub html: Option<String>,
+}
 
-use super::RuntimeEvent;
+impl ClipboardContents {
+    pub fn text_with_files(text: String, files: Vec<String>) -> Self {
+        let html = html_fragment(&text);
+        Self {
+            text,
+            files,
+            html: Some(html),
+        }
+    }
+}
 
 pub trait ClipboardAccess: Send {
     fn has_files(&self) -> bool;
     fn has_text(&self) -> bool;
+    fn has_format(&self, name: &str) -> bool;
     fn get_files(&self) -> Result<Vec<String>>;
     fn get_text(&self) -> Result<String>;
+    fn get_buffer(&self, name: &str) -> Result<Vec<u8>>;
     fn set_text(&self, text: String) -> Result<()>;
-}
-
-pub struct SystemClipboard {
-    context: ClipboardContext,
-}
+    fn set_contents(&self, contents: ClipboardContents) -> Result<()>;
 
-impl SystemClipboard {
-    pub fn new() -> Result<Self> {
-        Ok(Self {
-            context: ClipboardContext::new().map_err(|err| anyhow!(err.to_string()))?,
-        })
-    }
-}
-
-impl ClipboardAccess for SystemClipboard {
-    fn has_files(&self) -> bool {
-        self.context.has(ContentFormat::Files)
-    }
-
-    fn has_text(&self) -> bool {
-        self.context.has(ContentFormat::Text)
-    }
+    /// 处理一次剪贴板变化前调用, 之后的格式判断共用这次读取的格式列表
+    fn refresh_formats(&self) {}
 
-    fn get_files(&self) -> Result<Vec<String>> {
-        self.context
-            .get_files()
-            .map_err(|err| anyhow!(err.to_string()))
-    }
+    /// 格式判断结束后调用, 之后的读取重新列出格式, 不会沿用其他程序复制前的列表
+    fn forget_formats(&self) {}
 
-    fn get_text(&self) -> Result<String> {
-        self.context
-            .get_text()
-            .map_err(|err| anyhow!(err.to_string()))
-    }
+    fn is_private(&self) -> bool {
+        if EXCLUDE_FORMATS.iter().any(|format| self.has_format(format)) {
+            return true;
+        }
 
-    fn set_text(&self, text: String) -> Result<()> {
-        self.context
-            .set_text(text)
-            .map_err(|err| anyhow!(err.to_string()))
+        self.has_format(HISTORY_FORMAT)
+            && self
+                .get_buffer(HISTORY_FORMAT)
+                .ok()
+                .is_none_or(|data| data.iter().all(|byte| *byte == 0))
     }
 }
 
-pub struct ClipboardMonitor {
-    shutdown: Option<WatcherShutdown>,
-    thread: Option<thread::JoinHandle<()>>,
-}
-
-impl ClipboardMonitor {
-    pub fn spawn(sender: UnboundedSender<RuntimeEvent>) -> Result<Self> {
-        let (ready_sender, ready_receiver) = std::sync::mpsc::sync_channel(1);
-        let thread = thread::Builder::new()
-            .name("pathclip-clipboard".to_string())
-            .spawn(move || {
-                let mut watcher = match ClipboardWatcherContext::new() {
-                    Ok(watcher) => watcher,
-                    Err(err) => {
-                        let _ = ready_sender.send(Err(err.to_string()));
-                        return;
-                    }
-                };
-
-                let shutdown = watcher
-                    .add_handler(ChangeHandler { sender })
-                    .get_shutdown_channel();
-                if ready_sender.send(Ok(shutdown)).is_err() {
-                    return;
-                }
-
-                watcher.start_watch();
-            })
-            .context("failed to start clipboard watcher thread")?;
-
-        let shutdown = ready_receiver
-            .recv_timeout(Duration::from_secs(5))
-            .context("clipboard watcher did not start in time")?
-            .map_err(|err| anyhow!("failed to start clipboard watcher: {err}"))?;
-
-        Ok(Self {
-            shutdown: Some(shutdown),
-            thread: Some(thread),
+fn html_fragment(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            line.replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('"', "&quot;")
         })
-    }
-
-    pub async fn stop(mut self) {
-        if let Some(shutdown) = self.shutdown.take() {
-            shutdown.stop();
-        }
-        if let Some(thread) = self.thread.take() {
-            let _ = tokio::task::spawn_blocking(move || thread.join()).await;
-        }
-    }
-}
-
-struct ChangeHandler {
-    sender: UnboundedSender<RuntimeEvent>,
+        .collect::<Vec<_>>()
+        .join("<br>")
 }
 
-impl ClipboardHandler for ChangeHandler {
-    fn on_clipboard_change(&mut self) {
-        let _ = self.sender.send(RuntimeEvent::ClipboardChanged);
+#[cfg(test)]
+mod tests {
+    use super::ClipboardContents;
+
+    #[test]
+    fn html_fragment_escapes_each_line() {
+        let contents = ClipboardContents::text_with_files(
+            "/mnt/c/a&b.txt\r\n/mnt/c/<c>.txt".to_string(),
+            vec![r"C:\a&b.txt".to_string()],
+        );
+
+        assert_eq!(
+            contents.html.as_deref(),
+            Some("/mnt/c/a&amp;b.txt<br>/mnt/c/&lt;c&gt;.txt")
+        );
     }
 }
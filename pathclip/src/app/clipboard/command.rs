@@ -0,0 +1,649 @@
+use std::collections::hash_map::DefaultHasher;
+use std::env;
+use std::hash::{Hash, Hasher};
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{RecvTimeoutError, Sender};
+use std::sync::{Mutex, MutexGuard, Once, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow, bail};
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::warn;
+use url::Url;
+
+use super::{ClipboardAccess, ClipboardContents, EXCLUDE_FORMATS};
+use crate::app::RuntimeEvent;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+const TEXT_TYPES: [&str; 5] = [
+    "text/plain;charset=utf-8",
+    "UTF8_STRING",
+    "text/plain",
+    "STRING",
+    "TEXT",
+];
+const FILES_TYPE: &str = "text/uri-list";
+/// 轮询只比较内容的前若干字节, 不会每次都完整读取很大的剪贴板, 前缀之后的变化在格式列表变化前不会被发现
+const FINGERPRINT_LIMIT: usize = 64 * 1024;
+
+pub trait CommandRunner: Send {
+    fn run(&self, program: &str, args: &[&str], input: Option<&[u8]>) -> Result<Vec<u8>>;
+
+    /// 最多读取命令输出的前 `limit` 字节
+    fn run_prefix(&self, program: &str, args: &[&str], limit: usize) -> Result<Vec<u8>> {
+        let mut output = self.run(program, args, None)?;
+        output.truncate(limit);
+        Ok(output)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessRunner;
+
+impl CommandRunner for ProcessRunner {
+    fn run(&self, program: &str, args: &[&str], input: Option<&[u8]>) -> Result<Vec<u8>> {
+        let mut command = Command::new(program);
+        command.args(args).stderr(Stdio::null());
+
+        let Some(input) = input else {
+            let output = command
+                .stdin(Stdio::null())
+                .output()
+                .with_context(|| format!("failed to run `{program}`"))?;
+            if !output.status.success() {
+                bail!("`{program}` exited with {}", output.status);
+            }
+            return Ok(output.stdout);
+        };
+
+        // wl-copy 和 xclip 会 fork 出子进程持续提供剪贴板内容, stdout 不能是管道
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .with_context(|| format!("failed to run `{program}`"))?;
+        child
+            .stdin
+            .take()
+            .context("child stdin is not available")?
+            .write_all(input)
+            .with_context(|| format!("failed to write to `{program}`"))?;
+        let status = child
+            .wait()
+            .with_context(|| format!("failed to wait for `{program}`"))?;
+        if !status.success() {
+            bail!("`{program}` exited with {status}");
+        }
+        Ok(Vec::new())
+    }
+
+    fn run_prefix(&self, program: &str, args: &[&str], limit: usize) -> Result<Vec<u8>> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("failed to run `{program}`"))?;
+        let mut output = Vec::new();
+        let read = child
+            .stdout
+            .take()
+            .context("child stdout is not available")?
+            .take(limit as u64)
+            .read_to_end(&mut output);
+        if read.is_err() || output.len() == limit {
+            // 不需要剩余的输出, 直接结束进程而不是读完
+            let _ = child.kill();
+        }
+        let status = child
+            .wait()
+            .with_context(|| format!("failed to wait for `{program}`"))?;
+        read.with_context(|| format!("failed to read from `{program}`"))?;
+        if output.len() < limit && !status.success() {
+            bail!("`{program}` exited with {status}");
+        }
+        Ok(output)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Wayland,
+    X11,
+}
+
+impl Backend {
+    fn detect() -> Result<Self> {
+        let is_set = |name| env::var_os(name).is_some_and(|value| !value.is_empty());
+        if is_set("WAYLAND_DISPLAY") {
+            Ok(Self::Wayland)
+        } else if is_set("DISPLAY") {
+            Ok(Self::X11)
+        } else {
+            bail!("neither WAYLAND_DISPLAY nor DISPLAY is set")
+        }
+    }
+
+    fn list_command(self) -> (&'static str, Vec<&'static str>) {
+        match self {
+            Self::Wayland => ("wl-paste", vec!["--list-types"]),
+            Self::X11 => (
+                "xclip",
+                vec!["-selection", "clipboard", "-target", "TARGETS", "-out"],
+            ),
+        }
+    }
+
+    fn read_command(self, mime: &str) -> (&'static str, Vec<&str>) {
+        match self {
+            Self::Wayland => ("wl-paste", vec!["--no-newline", "--type", mime]),
+            Self::X11 => (
+                "xclip",
+                vec!["-selection", "clipboard", "-target", mime, "-out"],
+            ),
+        }
+    }
+
+    fn write_command(self, mime: &str) -> (&'static str, Vec<&str>) {
+        match self {
+            Self::Wayland => ("wl-copy", vec!["--type", mime]),
+            Self::X11 => (
+                "xclip",
+                vec!["-selection", "clipboard", "-target", mime, "-in"],
+            ),
+        }
+    }
+
+    fn text_type(self) -> &'static str {
+        match self {
+            Self::Wayland => TEXT_TYPES[0],
+            Self::X11 => TEXT_TYPES[1],
+        }
+    }
+}
+
+pub struct SystemClipboard<R = ProcessRunner> {
+    backend: Backend,
+    runner: R,
+    /// refresh_formats 读取的格式列表, forget_formats 或写入剪贴板后失效
+    types: Mutex<Option<Vec<String>>>,
+}
+
+impl SystemClipboard<ProcessRunner> {
+    pub fn new() -> Result<Self> {
+        Ok(Self::with_runner(Backend::detect()?, ProcessRunner))
+    }
+}
+
+impl<R: CommandRunner> SystemClipboard<R> {
+    pub fn with_runner(backend: Backend, runner: R) -> Self {
+        Self {
+            backend,
+            runner,
+            types: Mutex::new(None),
+        }
+    }
+
+    fn types(&self) -> Vec<String> {
+        if let Some(types) = self.cached_types().as_ref() {
+            return types.clone();
+        }
+        self.list_types()
+    }
+
+    fn cached_types(&self) -> MutexGuard<'_, Option<Vec<String>>> {
+        self.types.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn list_types(&self) -> Vec<String> {
+        let (program, args) = self.backend.list_command();
+        // 剪贴板为空时两个工具都会以错误退出
+        self.runner
+            .run(program, &args, None)
+            .map(|output| {
+                String::from_utf8_lossy(&output)
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn read(&self, mime: &str) -> Result<Vec<u8>> {
+        let (program, args) = self.backend.read_command(mime);
+        self.runner.run(program, &args, None)
+    }
+
+    fn write(&self, mime: &str, data: &[u8]) -> Result<()> {
+        *self.cached_types() = None;
+        let (program, args) = self.backend.write_command(mime);
+        self.runner.run(program, &args, Some(data)).map(|_| ())
+    }
+
+    fn text_type(&self, types: &[String]) -> Option<&'static str> {
+        TEXT_TYPES
+            .into_iter()
+            .find(|text_type| types.iter().any(|name| name == text_type))
+    }
+
+    fn fingerprint(&self) -> u64 {
+        let types = self.list_types();
+        let mut hasher = DefaultHasher::new();
+        types.hash(&mut hasher);
+        // 不能读取隐私内容, 只凭格式列表判断是否变化
+        if types
+            .iter()
+            .any(|name| EXCLUDE_FORMATS.contains(&name.as_str()))
+        {
+            return hasher.finish();
+        }
+        let content_type = if types.iter().any(|name| name == FILES_TYPE) {
+            Some(FILES_TYPE)
+        } else {
+            self.text_type(&types)
+        };
+        if let Some(content_type) = content_type {
+            let (program, args) = self.backend.read_command(content_type);
+            self.runner
+                .run_prefix(program, &args, FINGERPRINT_LIMIT)
+                .ok()
+                .hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+}
+
+impl<R: CommandRunner> ClipboardAccess for SystemClipboard<R> {
+    fn has_files(&self) -> bool {
+        self.has_format(FILES_TYPE)
+    }
+
+    fn has_text(&self) -> bool {
+        self.text_type(&self.types()).is_some()
+    }
+
+    fn has_format(&self, name: &str) -> bool {
+        self.types().iter().any(|format| format == name)
+    }
+
+    fn get_files(&self) -> Result<Vec<String>> {
+        let list = String::from_utf8(self.read(FILES_TYPE)?).context("file list is not UTF-8")?;
+        list.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                Url::parse(line)
+                    .ok()
+                    .and_then(|url| url.to_file_path().ok())
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .ok_or_else(|| anyhow!("clipboard file list contains a non-file URI"))
+            })
+            .collect()
+    }
+
+    fn get_text(&self) -> Result<String> {
+        let text_type = self
+            .text_type(&self.types())
+            .context("clipboard does not contain text")?;
+        String::from_utf8(self.read(text_type)?).context("clipboard text is not UTF-8")
+    }
+
+    fn get_buffer(&self, name: &str) -> Result<Vec<u8>> {
+        self.read(name)
+    }
+
+    fn set_text(&self, text: String) -> Result<()> {
+        self.write(self.backend.text_type(), text.as_bytes())
+    }
+
+    fn set_contents(&self, contents: ClipboardContents) -> Result<()> {
+        if !contents.text.is_empty() {
+            // wl-copy 和 xclip 每次只能提供一种格式, 第二次调用会替换第一次的内容
+            if !contents.files.is_empty() {
+                static WARNED: Once = Once::new();
+                WARNED.call_once(|| {
+                    warn!("keep_files is not supported by wl-copy and xclip, only the converted text is kept");
+                });
+            }
+            return self.set_text(contents.text);
+        }
+
+        let uris = contents
+            .files
+            .iter()
+            .map(|path| {
+                Url::from_file_path(path)
+                    .map(|url| url.to_string())
+                    .map_err(|_| anyhow!("`{path}` is not an absolute file path"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.write(FILES_TYPE, uris.join("\r\n").as_bytes())
+    }
+
+    fn refresh_formats(&self) {
+        let types = self.list_types();
+        *self.cached_types() = Some(types);
+    }
+
+    fn forget_formats(&self) {
+        *self.cached_types() = None;
+    }
+}
+
+pub struct ClipboardMonitor {
+    stop: Option<Sender<()>>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl ClipboardMonitor {
+    pub fn spawn(sender: UnboundedSender<RuntimeEvent>) -> Result<Self> {
+        Self::spawn_with(SystemClipboard::new()?, POLL_INTERVAL, sender)
+    }
+
+    fn spawn_with<R: CommandRunner + 'static>(
+        clipboard: SystemClipboard<R>,
+        interval: Duration,
+        sender: UnboundedSender<RuntimeEvent>,
+    ) -> Result<Self> {
+        let (stop_sender, stop_receiver) = std::sync::mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("pathclip-clipboard".to_string())
+            .spawn(move || {
+                let mut last = clipboard.fingerprint();
+                while let Err(RecvTimeoutError::Timeout) = stop_receiver.recv_timeout(interval) {
+                    let current = clipboard.fingerprint();
+                    if current != last {
+                        last = current;
+                        if sender.send(RuntimeEvent::ClipboardChanged).is_err() {
+                            return;
+                        }
+                    }
+                }
+            })
+            .context("failed to start clipboard polling thread")?;
+
+        Ok(Self {
+            stop: Some(stop_sender),
+            thread: Some(thread),
+        })
+    }
+
+    pub async fn stop(mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = tokio::task::spawn_blocking(move || thread.join()).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use anyhow::{Result, bail};
+    use tokio::sync::mpsc::unbounded_channel;
+
+    use super::{Backend, ClipboardMonitor, CommandRunner, ProcessRunner, SystemClipboard};
+    use crate::app::RuntimeEvent;
+    use crate::app::clipboard::{ClipboardAccess, ClipboardContents};
+
+    #[derive(Clone, Default)]
+    struct FakeRunner {
+        outputs: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+        writes: Arc<Mutex<Vec<(String, String)>>>,
+        reads: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl FakeRunner {
+        fn with(self, command: &str, output: &str) -> Self {
+            self.set(command, output);
+            self
+        }
+
+        fn set(&self, command: &str, output: &str) {
+            self.outputs
+                .lock()
+                .unwrap()
+                .insert(command.to_string(), output.as_bytes().to_vec());
+        }
+
+        fn writes(&self) -> Vec<(String, String)> {
+            self.writes.lock().unwrap().clone()
+        }
+    }
+
+    impl CommandRunner for FakeRunner {
+        fn run(&self, program: &str, args: &[&str], input: Option<&[u8]>) -> Result<Vec<u8>> {
+            let command = format!("{program} {}", args.join(" "));
+            if let Some(input) = input {
+                self.writes
+                    .lock()
+                    .unwrap()
+                    .push((command, String::from_utf8_lossy(input).into_owned()));
+                return Ok(Vec::new());
+            }
+            self.reads.lock().unwrap().push(command.clone());
+            match self.outputs.lock().unwrap().get(&command) {
+                Some(output) => Ok(output.clone()),
+                None => bail!("`{command}` failed"),
+            }
+        }
+    }
+
+    #[test]
+    fn reads_wayland_text_and_formats() {
+        let runner = FakeRunner::default()
+            .with(
+                "wl-paste --list-types",
+                "text/plain;charset=utf-8\nx-kde-passwordManagerHint\n",
+            )
+            .with(
+                "wl-paste --no-newline --type text/plain;charset=utf-8",
+                "C:\\a.txt",
+            )
+            .with(
+                "wl-paste --no-newline --type x-kde-passwordManagerHint",
+                "secret",
+            );
+        let clipboard = SystemClipboard::with_runner(Backend::Wayland, runner);
+
+        assert!(clipboard.has_text());
+        assert!(!clipboard.has_files());
+        assert_eq!(clipboard.get_text().unwrap(), "C:\\a.txt");
+        assert!(clipboard.is_private());
+    }
+
+    #[test]
+    fn refreshed_formats_are_listed_once() {
+        let runner = FakeRunner::default()
+            .with("wl-paste --list-types", "text/plain\n")
+            .with("wl-paste --no-newline --type text/plain", "C:\\a.txt");
+        let clipboard = SystemClipboard::with_runner(Backend::Wayland, runner.clone());
+
+        clipboard.refresh_formats();
+        assert!(!clipboard.is_private());
+        assert!(!clipboard.has_files());
+        assert!(clipboard.has_text());
+        assert_eq!(clipboard.get_text().unwrap(), "C:\\a.txt");
+        assert_eq!(
+            *runner.reads.lock().unwrap(),
+            vec![
+                "wl-paste --list-types",
+                "wl-paste --no-newline --type text/plain"
+            ]
+        );
+
+        // 其他程序复制后, forget_formats 之后的判断能看到新的格式
+        clipboard.forget_formats();
+        runner.set("wl-paste --list-types", "text/uri-list\n");
+        assert!(clipboard.has_files());
+
+        // 写入剪贴板后格式列表失效, 需要重新读取
+        clipboard.refresh_formats();
+        clipboard.set_text("C:/a.txt".to_string()).unwrap();
+        runner.set("wl-paste --list-types", "text/plain\n");
+        assert!(clipboard.has_text());
+        assert_eq!(runner.reads.lock().unwrap().len(), 5);
+    }
+
+    #[test]
+    fn reads_uri_list_as_files() {
+        let runner = FakeRunner::default()
+            .with(
+                "xclip -selection clipboard -target TARGETS -out",
+                "TARGETS\ntext/uri-list\nUTF8_STRING\n",
+            )
+            .with(
+                "xclip -selection clipboard -target text/uri-list -out",
+                "# copied\r\nfile:///mnt/c/My%20Docs/a.txt\r\nfile:///home/me/b.txt\r\n",
+            );
+        let clipboard = SystemClipboard::with_runner(Backend::X11, runner);
+
+        assert!(clipboard.has_files());
+        assert_eq!(
+            clipboard.get_files().unwrap(),
+            vec!["/mnt/c/My Docs/a.txt", "/home/me/b.txt"]
+        );
+    }
+
+    #[test]
+    fn empty_clipboard_has_no_content() {
+        let clipboard = SystemClipboard::with_runner(Backend::Wayland, FakeRunner::default());
+
+        assert!(!clipboard.has_text());
+        assert!(!clipboard.has_files());
+        assert!(clipboard.get_text().is_err());
+    }
+
+    #[test]
+    fn writes_text_or_file_list() {
+        let runner = FakeRunner::default();
+        let clipboard = SystemClipboard::with_runner(Backend::Wayland, runner.clone());
+
+        clipboard.set_text("/mnt/c/a.txt".to_string()).unwrap();
+        clipboard
+            .set_contents(ClipboardContents {
+                files: vec!["/home/me/a b.txt".to_string()],
+                ..ClipboardContents::default()
+            })
+            .unwrap();
+
+        assert_eq!(
+            runner.writes(),
+            vec![
+                (
+                    "wl-copy --type text/plain;charset=utf-8".to_string(),
+                    "/mnt/c/a.txt".to_string()
+                ),
+                (
+                    "wl-copy --type text/uri-list".to_string(),
+                    "file:///home/me/a%20b.txt".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn fingerprint_never_reads_private_content() {
+        let runner = FakeRunner::default()
+            .with(
+                "wl-paste --list-types",
+                "text/plain\nx-kde-passwordManagerHint\n",
+            )
+            .with("wl-paste --no-newline --type text/plain", "secret");
+        let clipboard = SystemClipboard::with_runner(Backend::Wayland, runner.clone());
+
+        let first = clipboard.fingerprint();
+        runner.set("wl-paste --no-newline --type text/plain", "other secret");
+
+        assert_eq!(clipboard.fingerprint(), first);
+        assert_eq!(
+            *runner.reads.lock().unwrap(),
+            vec!["wl-paste --list-types"; 2]
+        );
+    }
+
+    #[test]
+    fn fingerprint_compares_a_bounded_prefix() {
+        let prefix = "a".repeat(super::FINGERPRINT_LIMIT);
+        let runner = FakeRunner::default()
+            .with("wl-paste --list-types", "text/plain\n")
+            .with(
+                "wl-paste --no-newline --type text/plain",
+                &format!("{prefix}1"),
+            );
+        let clipboard = SystemClipboard::with_runner(Backend::Wayland, runner.clone());
+
+        let first = clipboard.fingerprint();
+        runner.set(
+            "wl-paste --no-newline --type text/plain",
+            &format!("{prefix}2"),
+        );
+        assert_eq!(clipboard.fingerprint(), first);
+
+        runner.set("wl-paste --no-newline --type text/plain", "b");
+        assert_ne!(clipboard.fingerprint(), first);
+    }
+
+    #[test]
+    fn text_with_files_writes_only_text() {
+        let runner = FakeRunner::default();
+        let clipboard = SystemClipboard::with_runner(Backend::X11, runner.clone());
+
+        clipboard
+            .set_contents(ClipboardContents::text_with_files(
+                "/mnt/c/a.txt".to_string(),
+                vec![r"C:\a.txt".to_string()],
+            ))
+            .unwrap();
+
+        assert_eq!(
+            runner.writes(),
+            vec![(
+                "xclip -selection clipboard -target UTF8_STRING -in".to_string(),
+                "/mnt/c/a.txt".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn process_runner_stops_reading_after_the_limit() {
+        let output = ProcessRunner.run_prefix("yes", &[], 10).unwrap();
+
+        assert_eq!(output, b"y\ny\ny\ny\ny\n");
+    }
+
+    #[tokio::test]
+    async fn monitor_reports_changed_content() {
+        let runner = FakeRunner::default()
+            .with("wl-paste --list-types", "text/plain\n")
+            .with("wl-paste --no-newline --type text/plain", "a");
+        let (sender, mut receiver) = unbounded_channel();
+        let monitor = ClipboardMonitor::spawn_with(
+            SystemClipboard::with_runner(Backend::Wayland, runner.clone()),
+            Duration::from_millis(5),
+            sender,
+        )
+        .unwrap();
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(receiver.try_recv().is_err());
+
+        runner.set("wl-paste --no-newline --type text/plain", "b");
+        let event = tokio::time::timeout(Duration::from_secs(1), receiver.recv())
+            .await
+            .unwrap();
+        assert!(matches!(event, Some(RuntimeEvent::ClipboardChanged)));
+
+        monitor.stop().await;
+    }
+}
@@ -0,0 +1,592 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use super::RuntimeEvent;
+use super::hotkey::HotkeyFailure;
+use super::settings::{Settings, parse_duration};
+
+const MAX_MESSAGE_BYTES: u64 = 64 * 1024;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Subcommand)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum Request {
+    #[command(about = "查看守护进程状态")]
+    Status,
+    #[command(about = "暂停自动转换")]
+    Pause {
+        #[arg(
+            long = "for",
+            value_name = "DURATION",
+            help = "暂停时长, 例如 30s, 5m. 默认使用配置中的 suspend_for"
+        )]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        duration: Option<String>,
+    },
+    #[command(about = "恢复自动转换")]
+    Resume,
+    #[command(about = "重新加载配置文件")]
+    Reload,
+    #[command(about = "使用指定 profile 转换当前剪贴板")]
+    Convert {
+        #[arg(long, value_name = "NAME", help = "profile 名称")]
+        profile: String,
+    },
+    #[command(about = "退出守护进程")]
+    Quit,
+}
+
+#[derive(Debug)]
+pub enum ControlCommand {
+    Status,
+    Pause(Option<Duration>),
+    Resume,
+    Reload(Box<Settings>),
+    Convert(String),
+    Quit,
+}
+
+#[derive(Debug)]
+pub struct ControlRequest {
+    pub command: ControlCommand,
+    pub reply: oneshot::Sender<Response>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<Status>,
+}
+
+impl Response {
+    pub fn ok(message: impl Into<String>) -> Self {
+        Self {
+            ok: true,
+            message: Some(message.into()),
+            status: None,
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            message: Some(message.into()),
+            status: None,
+        }
+    }
+
+    pub fn status(status: Status) -> Self {
+        Self {
+            ok: true,
+            message: None,
+            status: Some(status),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Status {
+    pub pid: u32,
+    pub auto_conversion: AutoConversion,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resume_in_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_profile: Option<String>,
+    pub profiles: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hotkey_failures: Vec<HotkeyFailure>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AutoConversion {
+    Active,
+    Paused,
+    Disabled,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "pid: {}", self.pid)?;
+        let state = match self.auto_conversion {
+            AutoConversion::Active => "active",
+            AutoConversion::Paused => "paused",
+            AutoConversion::Disabled => "disabled",
+        };
+        match self.resume_in_ms {
+            Some(ms) => writeln!(
+                f,
+                "auto conversion: {state} (resumes in {:?})",
+                Duration::from_millis(ms)
+            )?,
+            None => writeln!(f, "auto conversion: {state}")?,
+        }
+        writeln!(
+            f,
+            "auto profile: {}",
+            self.auto_profile.as_deref().unwrap_or("-")
+        )?;
+        writeln!(f, "profiles: {}", self.profiles.join(", "))?;
+        if !self.hotkey_failures.is_empty() {
+            writeln!(f, "unregistered hotkeys:")?;
+            for failure in &self.hotkey_failures {
+                writeln!(f, "  {failure}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct ControlServer {
+    task: JoinHandle<()>,
+}
+
+impl ControlServer {
+    pub fn spawn(sender: UnboundedSender<RuntimeEvent>, config: Option<PathBuf>) -> Result<Self> {
+        let listener = bind()?;
+        let handler = Handler { sender, config };
+        let task = tokio::spawn(accept_loop(listener, handler));
+        Ok(Self { task })
+    }
+
+    pub fn stop(self) {
+        self.task.abort();
+    }
+}
+
+pub async fn request(request: &Request) -> Result<Response> {
+    let stream = connect().await?;
+    tokio::time::timeout(REQUEST_TIMEOUT, exchange(stream, request))
+        .await
+        .context("pathclip did not respond in time")?
+}
+
+async fn exchange<S>(stream: S, request: &Request) -> Result<Response>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut payload = serde_json::to_string(request).context("failed to encode request")?;
+    payload.push('\n');
+    writer
+        .write_all(payload.as_bytes())
+        .await
+        .context("failed to send request")?;
+
+    let mut line = String::new();
+    BufReader::new(reader.take(MAX_MESSAGE_BYTES))
+        .read_line(&mut line)
+        .await
+        .context("failed to read response")?;
+    serde_json::from_str(&line).context("pathclip sent an invalid response")
+}
+
+#[derive(Clone)]
+struct Handler {
+    sender: UnboundedSender<RuntimeEvent>,
+    config: Option<PathBuf>,
+}
+
+impl Handler {
+    async fn serve<S>(self, stream: S)
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        if let Err(err) = self.serve_connection(stream).await {
+            debug!(error = ?err, "control connection failed");
+        }
+    }
+
+    async fn serve_connection<S>(&self, stream: S) -> Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let (reader, mut writer) = tokio::io::split(stream);
+        let mut line = String::new();
+        BufReader::new(reader.take(MAX_MESSAGE_BYTES))
+            .read_line(&mut line)
+            .await
+            .context("failed to read request")?;
+
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => {
+                debug!(?request, "control request received");
+                self.handle(request).await
+            }
+            Err(err) => Response::error(format!("invalid request: {err}")),
+        };
+
+        let mut payload = serde_json::to_string(&response).context("failed to encode response")?;
+        payload.push('\n');
+        writer
+            .write_all(payload.as_bytes())
+            .await
+            .context("failed to send response")?;
+        writer
+            .shutdown()
+            .await
+            .context("failed to close connection")
+    }
+
+    async fn handle(&self, request: Request) -> Response {
+        let command = match self.command(request) {
+            Ok(command) => command,
+            Err(err) => return Response::error(format!("{err:#}")),
+        };
+
+        let (reply, response) = oneshot::channel();
+        let request = ControlRequest { command, reply };
+        if self.sender.send(RuntimeEvent::Control(request)).is_err() {
+            return Response::error("pathclip is shutting down");
+        }
+        response
+            .await
+            .unwrap_or_else(|_| Response::error("pathclip is shutting down"))
+    }
+
+    fn command(&self, request: Request) -> Result<ControlCommand> {
+        Ok(match request {
+            Request::Status => ControlCommand::Status,
+            Request::Pause { duration } => ControlCommand::Pause(
+                parse_duration(duration.as_deref().unwrap_or_default())
+                    .context("invalid pause duration")?,
+            ),
+            Request::Resume => ControlCommand::Resume,
+            Request::Reload => ControlCommand::Reload(Box::new(
+                Settings::load(self.config.clone()).context("failed to reload settings")?,
+            )),
+            Request::Convert { profile } => ControlCommand::Convert(profile),
+            Request::Quit => ControlCommand::Quit,
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+type Listener = tokio::net::UnixListener;
+
+#[cfg(target_os = "linux")]
+fn socket_address() -> Result<std::os::unix::net::SocketAddr> {
+    use std::os::linux::net::SocketAddrExt;
+
+    let user = std::env::var("USER").unwrap_or_default();
+    std::os::unix::net::SocketAddr::from_abstract_name(format!("pathclip-control-{user}"))
+        .context("failed to create pathclip control socket address")
+}
+
+#[cfg(target_os = "linux")]
+fn bind() -> Result<Listener> {
+    let listener = std::os::unix::net::UnixListener::bind_addr(&socket_address()?)
+        .context("failed to create pathclip control socket")?;
+    listener
+        .set_nonblocking(true)
+        .context("failed to configure pathclip control socket")?;
+    Listener::from_std(listener).context("failed to register pathclip control socket")
+}
+
+#[cfg(target_os = "linux")]
+fn current_uid() -> Result<u32> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = std::fs::metadata("/proc/self").context("failed to look up the current user")?;
+    Ok(metadata.uid())
+}
+
+/// 抽象 socket 对所有用户可见, 用 `SO_PEERCRED` 检查对端是否为同一用户
+#[cfg(target_os = "linux")]
+fn is_same_user(stream: &tokio::net::UnixStream, uid: u32) -> bool {
+    stream.peer_cred().is_ok_and(|cred| cred.uid() == uid)
+}
+
+#[cfg(target_os = "linux")]
+async fn accept_loop(listener: Listener, handler: Handler) {
+    let uid = match current_uid() {
+        Ok(uid) => uid,
+        Err(err) => {
+            warn!(error = ?err, "control socket disabled");
+            return;
+        }
+    };
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) if is_same_user(&stream, uid) => {
+                tokio::spawn(handler.clone().serve(stream));
+            }
+            Ok(_) => warn!("rejected control connection from another user"),
+            Err(err) => warn!(error = ?err, "failed to accept control connection"),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+async fn connect() -> Result<tokio::net::UnixStream> {
+    let stream = std::os::unix::net::UnixStream::connect_addr(&socket_address()?)
+        .context("pathclip is not running")?;
+    stream
+        .set_nonblocking(true)
+        .context("failed to configure control connection")?;
+    let stream = tokio::net::UnixStream::from_std(stream)
+        .context("failed to register control connection")?;
+    if !is_same_user(&stream, current_uid()?) {
+        anyhow::bail!("pathclip control socket is owned by another user");
+    }
+    Ok(stream)
+}
+
+#[cfg(target_os = "windows")]
+type Listener = tokio::net::windows::named_pipe::NamedPipeServer;
+
+/// 用户 SID 在域之间也唯一, 不使用可能重名的 `USERNAME`
+#[cfg(target_os = "windows")]
+fn pipe_name(sid: &str) -> String {
+    format!(r"\\.\pipe\pathclip-control-{sid}")
+}
+
+/// 进程所属用户的 SID 字符串, pid 为 None 时查询当前进程
+#[cfg(target_os = "windows")]
+fn process_user_sid(pid: Option<u32>) -> Result<String> {
+    use windows::Win32::Foundation::{CloseHandle, HANDLE, HLOCAL, LocalFree};
+    use windows::Win32::Security::Authorization::ConvertSidToStringSidW;
+    use windows::Win32::Security::{GetTokenInformation, TOKEN_QUERY, TOKEN_USER, TokenUser};
+    use windows::Win32::System::Threading::{
+        GetCurrentProcess, OpenProcess, OpenProcessToken, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    use windows::core::PWSTR;
+
+    unsafe {
+        let process = match pid {
+            Some(pid) => OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid)
+                .with_context(|| format!("failed to open process {pid}"))?,
+            None => GetCurrentProcess(),
+        };
+        let mut token = HANDLE::default();
+        let opened = OpenProcessToken(process, TOKEN_QUERY, &mut token);
+        if pid.is_some() {
+            let _ = CloseHandle(process);
+        }
+        opened.context("failed to open process token")?;
+
+        let mut length = 0;
+        let _ = GetTokenInformation(token, TokenUser, None, 0, &mut length);
+        // TOKEN_USER 包含指针, 缓冲区按 u64 对齐
+        let mut buffer = vec![0u64; (length as usize).div_ceil(size_of::<u64>())];
+        let queried = GetTokenInformation(
+            token,
+            TokenUser,
+            Some(buffer.as_mut_ptr().cast()),
+            length,
+            &mut length,
+        );
+        let _ = CloseHandle(token);
+        queried.context("failed to query token user")?;
+
+        let user = &*buffer.as_ptr().cast::<TOKEN_USER>();
+        let mut sid = PWSTR::null();
+        ConvertSidToStringSidW(user.User.Sid, &mut sid).context("failed to format user SID")?;
+        let result = sid.to_string().context("user SID is not valid UTF-16");
+        let _ = LocalFree(Some(HLOCAL(sid.0.cast())));
+        result
+    }
+}
+
+/// 命名管道对端进程是否属于 sid 对应的用户, server 为 true 时检查服务端进程
+#[cfg(target_os = "windows")]
+fn is_same_user(pipe: &impl std::os::windows::io::AsRawHandle, server: bool, sid: &str) -> bool {
+    use windows::Win32::Foundation::HANDLE;
+    use windows::Win32::System::Pipes::{GetNamedPipeClientProcessId, GetNamedPipeServerProcessId};
+
+    let handle = HANDLE(pipe.as_raw_handle());
+    let mut pid = 0;
+    let found = unsafe {
+        if server {
+            GetNamedPipeServerProcessId(handle, &mut pid)
+        } else {
+            GetNamedPipeClientProcessId(handle, &mut pid)
+        }
+    };
+    found.is_ok() && process_user_sid(Some(pid)).is_ok_and(|peer| peer == sid)
+}
+
+#[cfg(target_os = "windows")]
+fn bind() -> Result<Listener> {
+    let sid = process_user_sid(None)?;
+    tokio::net::windows::named_pipe::ServerOptions::new()
+        .first_pipe_instance(true)
+        .create(pipe_name(&sid))
+        .context("failed to create pathclip control pipe")
+}
+
+#[cfg(target_os = "windows")]
+async fn accept_loop(mut server: Listener, handler: Handler) {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let sid = match process_user_sid(None) {
+        Ok(sid) => sid,
+        Err(err) => {
+            warn!(error = ?err, "control pipe disabled");
+            return;
+        }
+    };
+    loop {
+        let connected = server.connect().await;
+        let next = match ServerOptions::new().create(pipe_name(&sid)) {
+            Ok(next) => next,
+            Err(err) => {
+                warn!(error = ?err, "failed to create pathclip control pipe");
+                return;
+            }
+        };
+        // 连接失败的实例不能复用, 换成新创建的实例后丢弃
+        let previous = std::mem::replace(&mut server, next);
+        match connected {
+            Ok(()) if is_same_user(&previous, false, &sid) => {
+                tokio::spawn(handler.clone().serve(previous));
+            }
+            Ok(()) => warn!("rejected control connection from another user"),
+            Err(err) => warn!(error = ?err, "failed to accept control connection"),
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+async fn connect() -> Result<tokio::net::windows::named_pipe::NamedPipeClient> {
+    let sid = process_user_sid(None)?;
+    let client = tokio::net::windows::named_pipe::ClientOptions::new()
+        .open(pipe_name(&sid))
+        .context("pathclip is not running")?;
+    // 管道名称可以被其他用户抢先创建, 只把请求发给同一用户的进程
+    if !is_same_user(&client, true, &sid) {
+        anyhow::bail!("pathclip control pipe is owned by another user");
+    }
+    Ok(client)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::sync::mpsc::unbounded_channel;
+
+    use super::{ControlCommand, Handler, Request, Response, exchange};
+    use crate::app::RuntimeEvent;
+
+    fn new_handler() -> (Handler, tokio::sync::mpsc::UnboundedReceiver<RuntimeEvent>) {
+        let (sender, receiver) = unbounded_channel();
+        (
+            Handler {
+                sender,
+                config: None,
+            },
+            receiver,
+        )
+    }
+
+    #[tokio::test]
+    async fn client_request_is_forwarded_to_runner() {
+        let (handler, mut receiver) = new_handler();
+        let (client, server) = tokio::io::duplex(1024);
+        tokio::spawn(handler.serve(server));
+        let runner = tokio::spawn(async move {
+            let Some(RuntimeEvent::Control(request)) = receiver.recv().await else {
+                panic!("expected control request");
+            };
+            let ControlCommand::Pause(duration) = request.command else {
+                panic!("expected pause command");
+            };
+            request.reply.send(Response::ok("paused")).unwrap();
+            duration
+        });
+
+        let response = exchange(
+            client,
+            &Request::Pause {
+                duration: Some("5m".to_string()),
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response, Response::ok("paused"));
+        assert_eq!(runner.await.unwrap(), Some(Duration::from_secs(300)));
+    }
+
+    #[tokio::test]
+    async fn invalid_requests_get_error_responses() {
+        let (handler, _receiver) = new_handler();
+        let (client, server) = tokio::io::duplex(1024);
+        tokio::spawn(handler.serve(server));
+        let response = exchange(
+            client,
+            &Request::Pause {
+                duration: Some("soon".to_string()),
+            },
+        )
+        .await
+        .unwrap();
+        assert!(!response.ok);
+        assert!(response.message.unwrap().contains("invalid pause duration"));
+
+        let (handler, _receiver) = new_handler();
+        let (mut client, server) = tokio::io::duplex(1024);
+        tokio::spawn(handler.serve(server));
+        client
+            .write_all(b"{\"command\":\"launch\"}\n")
+            .await
+            .unwrap();
+        let mut line = String::new();
+        BufReader::new(client).read_line(&mut line).await.unwrap();
+        let response: Response = serde_json::from_str(&line).unwrap();
+        assert!(!response.ok);
+        assert!(response.message.unwrap().starts_with("invalid request"));
+    }
+
+    #[tokio::test]
+    async fn stopped_runner_is_reported() {
+        let (handler, receiver) = new_handler();
+        drop(receiver);
+        let (client, server) = tokio::io::duplex(1024);
+        tokio::spawn(handler.serve(server));
+
+        let response = exchange(client, &Request::Status).await.unwrap();
+
+        assert_eq!(response, Response::error("pathclip is shutting down"));
+    }
+
+    #[test]
+    fn requests_use_command_tag() {
+        assert_eq!(
+            serde_json::to_string(&Request::Convert {
+                profile: "wsl".to_string()
+            })
+            .unwrap(),
+            r#"{"command":"convert","profile":"wsl"}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Request>(r#"{"command":"pause"}"#).unwrap(),
+            Request::Pause { duration: None }
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn peers_of_other_users_are_rejected() {
+        let (stream, _peer) = tokio::net::UnixStream::pair().unwrap();
+        let uid = super::current_uid().unwrap();
+
+        assert!(super::is_same_user(&stream, uid));
+        assert!(!super::is_same_user(&stream, uid.wrapping_add(1)));
+    }
+}
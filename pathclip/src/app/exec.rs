@@ -0,0 +1,161 @@
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, anyhow, bail};
+
+const STDERR_LIMIT: u64 = 4096;
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecCommand {
+    pub program: String,
+    pub args: Vec<String>,
+    pub timeout: Duration,
+    pub max_output_bytes: usize,
+}
+
+impl ExecCommand {
+    pub fn run(&self, input: &str) -> Result<String> {
+        let program = &self.program;
+        let mut child = Command::new(program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("failed to start `{program}`"))?;
+
+        let mut stdin = child.stdin.take().context("child stdin is not piped")?;
+        let input = input.as_bytes().to_vec();
+        thread::spawn(move || {
+            let _ = stdin.write_all(&input);
+        });
+
+        let stdout = child.stdout.take().context("child stdout is not piped")?;
+        let limit = self.max_output_bytes as u64 + 1;
+        let (stdout_tx, stdout_rx) = mpsc::channel();
+        thread::spawn(move || {
+            let mut output = Vec::new();
+            let _ = stdout_tx.send(stdout.take(limit).read_to_end(&mut output).map(|_| output));
+        });
+
+        let mut stderr = child.stderr.take().context("child stderr is not piped")?;
+        let (stderr_tx, stderr_rx) = mpsc::channel();
+        thread::spawn(move || {
+            let mut output = Vec::new();
+            let _ = (&mut stderr).take(STDERR_LIMIT).read_to_end(&mut output);
+            let _ = stderr_tx.send(output);
+            let _ = io::copy(&mut stderr, &mut io::sink());
+        });
+
+        let deadline = Instant::now() + self.timeout;
+        let status = loop {
+            if let Some(status) = child.try_wait().context("failed to wait for command")? {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                bail!("`{program}` did not finish within {:?}", self.timeout);
+            }
+            thread::sleep(POLL_INTERVAL);
+        };
+
+        // 命令留下的后台进程可能一直占用 stdout, 读取线程不 join, 只等待到截止时间为止
+        let output = stdout_rx
+            .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            .map_err(|_| anyhow!("`{program}` did not close stdout within {:?}", self.timeout))?
+            .context("failed to read command output")?;
+        if output.len() > self.max_output_bytes {
+            bail!(
+                "`{program}` wrote more than {} bytes to stdout",
+                self.max_output_bytes
+            );
+        }
+        if !status.success() {
+            let stderr = stderr_rx
+                .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                .unwrap_or_default();
+            let stderr = String::from_utf8_lossy(&stderr);
+            match stderr.trim() {
+                "" => bail!("`{program}` exited with {status}"),
+                message => bail!("`{program}` exited with {status}: {message}"),
+            }
+        }
+
+        let output =
+            String::from_utf8(output).map_err(|_| anyhow!("`{program}` wrote non-UTF-8 output"))?;
+        let output = output.strip_suffix('\n').unwrap_or(&output);
+        Ok(output.strip_suffix('\r').unwrap_or(output).to_string())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::ExecCommand;
+
+    fn sh(script: &str) -> ExecCommand {
+        ExecCommand {
+            program: "sh".to_string(),
+            args: vec!["-c".to_string(), script.to_string()],
+            timeout: Duration::from_secs(5),
+            max_output_bytes: 64,
+        }
+    }
+
+    #[test]
+    fn pipes_input_through_the_command() {
+        let command = sh("sed 's|^C:|//depot|; s|\\\\|/|g'");
+        assert_eq!(
+            command.run(r"C:\src\main.rs").unwrap(),
+            "//depot/src/main.rs"
+        );
+    }
+
+    #[test]
+    fn non_zero_exit_is_an_error_with_stderr() {
+        let error = sh("echo 'not mapped' >&2; exit 3").run("x").unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains("exit status: 3"), "{message}");
+        assert!(message.contains("not mapped"), "{message}");
+    }
+
+    #[test]
+    fn slow_command_is_killed_after_timeout() {
+        let mut command = sh("sleep 5");
+        command.timeout = Duration::from_millis(100);
+
+        let started = Instant::now();
+        let error = command.run("x").unwrap_err();
+
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert!(error.to_string().contains("did not finish"));
+    }
+
+    #[test]
+    fn leftover_process_holding_stdout_does_not_block() {
+        let mut command = sh("sleep 5 & echo done");
+        command.timeout = Duration::from_millis(200);
+
+        let started = Instant::now();
+        let error = command.run("x").unwrap_err();
+
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert!(
+            error.to_string().contains("did not close stdout"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn oversized_output_is_rejected() {
+        let error = sh("yes path | head -c 1000").run("x").unwrap_err();
+        assert!(error.to_string().contains("more than 64 bytes"));
+        assert_eq!(sh("printf '%64s' ''").run("x").unwrap().len(), 64);
+    }
+}
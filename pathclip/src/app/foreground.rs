@@ -0,0 +1,82 @@
+#[cfg(target_os = "windows")]
+use windows::Win32::Foundation::{CloseHandle, HWND};
+#[cfg(target_os = "windows")]
+use windows::Win32::System::Threading::{
+    OpenProcess, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION, QueryFullProcessImageNameW,
+};
+#[cfg(target_os = "windows")]
+use windows::Win32::UI::WindowsAndMessaging::{
+    GetForegroundWindow, GetWindowTextW, GetWindowThreadProcessId,
+};
+#[cfg(target_os = "windows")]
+use windows::core::PWSTR;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForegroundApp {
+    pub process: String,
+    pub window_title: String,
+}
+
+pub trait ForegroundLookup: Send {
+    fn current(&self) -> Option<ForegroundApp>;
+}
+
+pub struct SystemForeground;
+
+#[cfg(not(target_os = "windows"))]
+impl ForegroundLookup for SystemForeground {
+    fn current(&self) -> Option<ForegroundApp> {
+        None
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl ForegroundLookup for SystemForeground {
+    fn current(&self) -> Option<ForegroundApp> {
+        let window = unsafe { GetForegroundWindow() };
+        if window.is_invalid() {
+            return None;
+        }
+
+        Some(ForegroundApp {
+            process: process_name(window).unwrap_or_default(),
+            window_title: window_title(window),
+        })
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn window_title(window: HWND) -> String {
+    let mut buffer = [0u16; 512];
+    let len = unsafe { GetWindowTextW(window, &mut buffer) };
+    String::from_utf16_lossy(&buffer[..len.max(0) as usize])
+}
+
+#[cfg(target_os = "windows")]
+fn process_name(window: HWND) -> Option<String> {
+    let mut process_id = 0;
+    unsafe { GetWindowThreadProcessId(window, Some(&mut process_id)) };
+    if process_id == 0 {
+        return None;
+    }
+
+    let handle =
+        unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id) }.ok()?;
+    let mut buffer = [0u16; 1024];
+    let mut len = buffer.len() as u32;
+    let result = unsafe {
+        QueryFullProcessImageNameW(
+            handle,
+            PROCESS_NAME_WIN32,
+            PWSTR(buffer.as_mut_ptr()),
+            &mut len,
+        )
+    };
+    unsafe {
+        let _ = CloseHandle(handle);
+    }
+    result.ok()?;
+
+    let path = String::from_utf16_lossy(&buffer[..len as usize]);
+    path.rsplit(['\\', '/']).next().map(str::to_string)
+}
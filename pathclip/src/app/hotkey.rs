@@ -1,120 +1,182 @@
-use std::thread;
-use std::time::Duration;
-
-use anyhow::{Context, Result, anyhow};
-use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
-use global_hotkey::hotkey::HotKey;
-use tokio::sync::mpsc::UnboundedSender;
-use winit::application::ApplicationHandler;
-use winit::event::WindowEvent;
-use winit::event_loop::{ActiveEventLoop, EventLoop, EventLoopProxy};
-use winit::platform::windows::EventLoopBuilderExtWindows;
-use winit::window::WindowId;
-
-use super::RuntimeEvent;
-
-enum ControlEvent {
-    Stop,
+#[cfg(target_os = "linux")]
+mod unsupported;
+#[cfg(target_os = "windows")]
+mod win32;
+
+use std::fmt;
+
+use anyhow::Result;
+use global_hotkey::hotkey::{Code, HotKey, Modifiers};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+#[cfg(target_os = "linux")]
+pub use unsupported::HotkeyMonitor;
+#[cfg(target_os = "windows")]
+pub use win32::HotkeyMonitor;
+
+/// Windows 自己处理的组合键, 注册会失败或者永远不会触发
+const RESERVED: [(Modifiers, Code, &str); 11] = [
+    (Modifiers::SUPER, Code::KeyL, "locks the workstation"),
+    (Modifiers::SUPER, Code::KeyD, "shows the desktop"),
+    (Modifiers::SUPER, Code::KeyE, "opens File Explorer"),
+    (Modifiers::SUPER, Code::KeyR, "opens the Run dialog"),
+    (Modifiers::SUPER, Code::KeyX, "opens the Quick Link menu"),
+    (Modifiers::SUPER, Code::Tab, "opens Task View"),
+    (Modifiers::ALT, Code::Tab, "switches windows"),
+    (Modifiers::CONTROL, Code::Escape, "opens the Start menu"),
+    (
+        Modifiers::CONTROL.union(Modifiers::ALT),
+        Code::Delete,
+        "opens the security screen",
+    ),
+    (
+        Modifiers::CONTROL.union(Modifiers::SHIFT),
+        Code::Escape,
+        "opens Task Manager",
+    ),
+    (Modifiers::empty(), Code::F12, "is reserved for debuggers"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HotkeyBinding {
+    pub owner: String,
+    pub hotkey: HotKey,
 }
 
-pub struct HotkeyMonitor {
-    proxy: EventLoopProxy<ControlEvent>,
-    thread: Option<thread::JoinHandle<()>>,
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HotkeyFailure {
+    pub owner: String,
+    pub hotkey: String,
+    pub error: String,
 }
 
-impl HotkeyMonitor {
-    pub fn spawn(
-        hotkeys: Vec<HotKey>,
-        sender: UnboundedSender<RuntimeEvent>,
-    ) -> Result<Option<Self>> {
-        if hotkeys.is_empty() {
-            return Ok(None);
-        }
-
-        let (ready_sender, ready_receiver) = std::sync::mpsc::sync_channel(1);
-        let thread = thread::Builder::new()
-            .name("pathclip-hotkeys".to_string())
-            .spawn(move || {
-                let mut builder = EventLoop::<ControlEvent>::with_user_event();
-                builder.with_any_thread(true);
-                let event_loop = match builder.build() {
-                    Ok(event_loop) => event_loop,
-                    Err(err) => {
-                        let _ = ready_sender.send(Err(err.to_string()));
-                        return;
-                    }
-                };
-
-                let proxy = event_loop.create_proxy();
-                let manager = match GlobalHotKeyManager::new() {
-                    Ok(manager) => manager,
-                    Err(err) => {
-                        let _ = ready_sender.send(Err(err.to_string()));
-                        return;
-                    }
-                };
-
-                if let Err(err) = manager.register_all(&hotkeys) {
-                    let _ = ready_sender.send(Err(err.to_string()));
-                    return;
-                }
-
-                GlobalHotKeyEvent::set_event_handler(Some(move |event: GlobalHotKeyEvent| {
-                    if event.state == HotKeyState::Pressed {
-                        let _ = sender.send(RuntimeEvent::HotkeyPressed(event.id));
-                    }
-                }));
-
-                if ready_sender.send(Ok(proxy)).is_err() {
-                    return;
-                }
-
-                let mut app = HotkeyApp { manager, hotkeys };
-                if let Err(err) = event_loop.run_app(&mut app) {
-                    tracing::error!(error = ?err, "global hotkey event loop exited with an error");
-                }
-            })
-            .context("failed to start global hotkey thread")?;
-
-        let proxy = ready_receiver
-            .recv_timeout(Duration::from_secs(5))
-            .context("global hotkey thread did not start in time")?
-            .map_err(|err| anyhow!("failed to start global hotkeys: {err}"))?;
-
-        Ok(Some(Self {
-            proxy,
-            thread: Some(thread),
-        }))
+impl fmt::Display for HotkeyFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} `{}`: {}", self.owner, self.hotkey, self.error)
     }
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub trait Registrar {
+    fn register(&mut self, hotkey: HotKey) -> Result<()>;
+}
 
-    pub async fn stop(mut self) {
-        let _ = self.proxy.send_event(ControlEvent::Stop);
-        if let Some(thread) = self.thread.take() {
-            let _ = tokio::task::spawn_blocking(move || thread.join()).await;
+/// 逐个注册热键, 被其他程序占用的组合键只影响使用它的 profile
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn register_each(
+    registrar: &mut impl Registrar,
+    bindings: &[HotkeyBinding],
+) -> (Vec<HotKey>, Vec<HotkeyFailure>) {
+    let mut registered = Vec::new();
+    let mut failures = Vec::new();
+    for binding in bindings {
+        if let Some(reason) = reserved_reason(binding.hotkey) {
+            warn!(
+                owner = %binding.owner,
+                hotkey = %binding.hotkey,
+                "hotkey is reserved by Windows ({reason}) and may never fire"
+            );
+        }
+        match registrar.register(binding.hotkey) {
+            Ok(()) => registered.push(binding.hotkey),
+            Err(err) => {
+                warn!(
+                    owner = %binding.owner,
+                    hotkey = %binding.hotkey,
+                    error = ?err,
+                    "failed to register hotkey"
+                );
+                failures.push(HotkeyFailure {
+                    owner: binding.owner.clone(),
+                    hotkey: binding.hotkey.to_string(),
+                    error: format!("{err:#}"),
+                });
+            }
         }
     }
+    (registered, failures)
 }
 
-struct HotkeyApp {
-    manager: GlobalHotKeyManager,
-    hotkeys: Vec<HotKey>,
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn reserved_reason(hotkey: HotKey) -> Option<&'static str> {
+    RESERVED
+        .iter()
+        .find(|(mods, key, _)| HotKey::new(Some(*mods), *key) == hotkey)
+        .map(|(_, _, reason)| *reason)
 }
 
-impl ApplicationHandler<ControlEvent> for HotkeyApp {
-    fn resumed(&mut self, _event_loop: &ActiveEventLoop) {}
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use anyhow::{Result, bail};
+    use global_hotkey::hotkey::HotKey;
+
+    use super::{HotkeyBinding, Registrar, register_each, reserved_reason};
 
-    fn user_event(&mut self, event_loop: &ActiveEventLoop, event: ControlEvent) {
-        if matches!(event, ControlEvent::Stop) {
-            let _ = self.manager.unregister_all(&self.hotkeys);
-            event_loop.exit();
+    #[derive(Default)]
+    struct FakeRegistrar {
+        taken: HashSet<u32>,
+        registered: Vec<u32>,
+    }
+
+    impl Registrar for FakeRegistrar {
+        fn register(&mut self, hotkey: HotKey) -> Result<()> {
+            if self.taken.contains(&hotkey.id()) {
+                bail!("hotkey `{hotkey}` is already registered by another application");
+            }
+            self.registered.push(hotkey.id());
+            Ok(())
         }
     }
 
-    fn window_event(
-        &mut self,
-        _event_loop: &ActiveEventLoop,
-        _window_id: WindowId,
-        _event: WindowEvent,
-    ) {
+    fn binding(owner: &str, hotkey: &str) -> HotkeyBinding {
+        HotkeyBinding {
+            owner: owner.to_string(),
+            hotkey: hotkey.parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn taken_hotkey_only_fails_its_owner() {
+        let bindings = [
+            binding("profile `slash`", "Ctrl+Shift+V"),
+            binding("profile `wsl`", "Ctrl+Alt+W"),
+            binding("toggle_hotkey", "Ctrl+Alt+P"),
+        ];
+        let mut registrar = FakeRegistrar {
+            taken: HashSet::from([bindings[1].hotkey.id()]),
+            ..FakeRegistrar::default()
+        };
+
+        let (registered, failures) = register_each(&mut registrar, &bindings);
+
+        assert_eq!(registered, [bindings[0].hotkey, bindings[2].hotkey]);
+        assert_eq!(
+            registrar.registered,
+            [bindings[0].hotkey.id(), bindings[2].hotkey.id()]
+        );
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].owner, "profile `wsl`");
+        assert_eq!(failures[0].hotkey, bindings[1].hotkey.to_string());
+        assert!(failures[0].error.contains("another application"));
+        assert!(failures[0].to_string().starts_with("profile `wsl`"));
+    }
+
+    #[test]
+    fn reserved_combos_are_detected() {
+        let lock = "Super+L".parse::<HotKey>().unwrap();
+        assert_eq!(reserved_reason(lock), Some("locks the workstation"));
+        assert!(reserved_reason("Ctrl+Shift+Escape".parse().unwrap()).is_some());
+        assert!(reserved_reason("F12".parse().unwrap()).is_some());
+        assert_eq!(reserved_reason("Ctrl+Super+L".parse().unwrap()), None);
+        assert_eq!(reserved_reason("Ctrl+Shift+V".parse().unwrap()), None);
+
+        let mut registrar = FakeRegistrar::default();
+        let (registered, failures) =
+            register_each(&mut registrar, &[binding("profile `lock`", "Super+L")]);
+        assert_eq!(registered, [lock]);
+        assert!(failures.is_empty());
     }
 }
@@ -0,0 +1,33 @@
+use anyhow::Result;
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::warn;
+
+use super::{HotkeyBinding, HotkeyFailure};
+use crate::app::RuntimeEvent;
+
+pub struct HotkeyMonitor;
+
+impl HotkeyMonitor {
+    pub fn spawn(
+        bindings: Vec<HotkeyBinding>,
+        _sender: UnboundedSender<RuntimeEvent>,
+    ) -> Result<(Option<Self>, Vec<HotkeyFailure>)> {
+        if !bindings.is_empty() {
+            warn!(
+                count = bindings.len(),
+                "global hotkeys are only supported on Windows and were ignored"
+            );
+        }
+        let failures = bindings
+            .into_iter()
+            .map(|binding| HotkeyFailure {
+                owner: binding.owner,
+                hotkey: binding.hotkey.to_string(),
+                error: "global hotkeys are only supported on Windows".to_string(),
+            })
+            .collect();
+        Ok((None, failures))
+    }
+
+    pub async fn stop(self) {}
+}
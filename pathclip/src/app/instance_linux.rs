@@ -0,0 +1,27 @@
+use std::env;
+use std::os::linux::net::SocketAddrExt;
+use std::os::unix::net::{SocketAddr, UnixListener};
+
+use anyhow::{Context, Result, bail};
+
+pub struct InstanceGuard {
+    _listener: UnixListener,
+}
+
+impl InstanceGuard {
+    pub fn acquire() -> Result<Self> {
+        let user = env::var("USER").unwrap_or_default();
+        let address = SocketAddr::from_abstract_name(format!("pathclip-single-instance-{user}"))
+            .context("failed to create pathclip instance socket address")?;
+
+        match UnixListener::bind_addr(&address) {
+            Ok(listener) => Ok(Self {
+                _listener: listener,
+            }),
+            Err(err) if err.kind() == std::io::ErrorKind::AddrInUse => {
+                bail!("another pathclip instance is already running")
+            }
+            Err(err) => Err(err).context("failed to create pathclip instance socket"),
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use anyhow::{Result, bail};
+#[cfg(target_os = "windows")]
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    GetAsyncKeyState, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBD_EVENT_FLAGS, KEYBDINPUT,
+    KEYEVENTF_KEYUP, SendInput, VIRTUAL_KEY, VK_CONTROL, VK_LWIN, VK_MENU, VK_RWIN, VK_SHIFT, VK_V,
+};
+
+pub trait KeyboardInput: Send {
+    fn modifiers_released(&self) -> bool;
+    fn send_paste(&self) -> Result<()>;
+}
+
+pub struct SystemKeyboard;
+
+#[cfg(not(target_os = "windows"))]
+impl KeyboardInput for SystemKeyboard {
+    fn modifiers_released(&self) -> bool {
+        true
+    }
+
+    fn send_paste(&self) -> Result<()> {
+        bail!("pasting is only supported on Windows")
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl KeyboardInput for SystemKeyboard {
+    fn modifiers_released(&self) -> bool {
+        [VK_SHIFT, VK_MENU, VK_LWIN, VK_RWIN]
+            .into_iter()
+            .all(|key| unsafe { GetAsyncKeyState(i32::from(key.0)) } >= 0)
+    }
+
+    fn send_paste(&self) -> Result<()> {
+        let inputs = [
+            key_input(VK_CONTROL, false),
+            key_input(VK_V, false),
+            key_input(VK_V, true),
+            key_input(VK_CONTROL, true),
+        ];
+        let sent = unsafe { SendInput(&inputs, size_of::<INPUT>() as i32) };
+        if sent as usize != inputs.len() {
+            bail!("failed to inject Ctrl+V into the foreground window");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn key_input(key: VIRTUAL_KEY, release: bool) -> INPUT {
+    INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: key,
+                dwFlags: if release {
+                    KEYEVENTF_KEYUP
+                } else {
+                    KEYBD_EVENT_FLAGS(0)
+                },
+                ..Default::default()
+            },
+        },
+    }
+}
@@ -1,19 +1,30 @@
+mod audit;
 mod clipboard;
+mod control;
+mod exec;
+mod foreground;
 mod hotkey;
+#[cfg_attr(target_os = "linux", path = "instance_linux.rs")]
 mod instance;
+mod keyboard;
 mod logging;
 mod runner;
+mod script;
 mod settings;
 mod transform;
 
+use std::fs::File;
+use std::io::BufReader;
 use std::path::PathBuf;
 
-use anyhow::{Context, Result};
-use clap::Parser;
+use anyhow::{Context, Result, bail};
+use clap::{Parser, Subcommand};
 use tokio::sync::mpsc::unbounded_channel;
 use tracing::{info, warn};
 
-use clipboard::ClipboardMonitor;
+use audit::Stats;
+use clipboard::{ClipboardMonitor, SystemClipboard};
+use control::{ControlRequest, ControlServer, Request, Response};
 use hotkey::HotkeyMonitor;
 use instance::InstanceGuard;
 use runner::Runner;
@@ -22,14 +33,16 @@ use settings::Settings;
 #[derive(Debug)]
 pub(super) enum RuntimeEvent {
     ClipboardChanged,
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
     HotkeyPressed(u32),
+    Control(ControlRequest),
 }
 
 #[derive(Debug, Parser)]
 #[command(
     name = "pathclip",
     version,
-    about = "自动转换 Windows 剪贴板路径",
+    about = "自动转换剪贴板中的 Windows 路径",
     after_help = "配置文件: ~/.config/pathclip/config.toml\n空 hotkey 表示不注册该 profile 的热键."
 )]
 struct Args {
@@ -38,6 +51,27 @@ struct Args {
 
     #[arg(long, help = "输出默认配置并退出")]
     print_default_config: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    #[command(about = "控制正在运行的守护进程")]
+    Ctl {
+        #[command(subcommand)]
+        request: Request,
+    },
+    #[command(about = "按 profile 和日期汇总审计日志")]
+    Stats {
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "指定审计日志路径, 默认使用配置中的 audit_log"
+        )]
+        log: Option<PathBuf>,
+    },
 }
 
 pub async fn run() -> Result<()> {
@@ -52,23 +86,56 @@ pub async fn run() -> Result<()> {
         return Ok(());
     }
 
-    let settings = Settings::load(args.config)?;
-    let _instance = InstanceGuard::acquire()?;
+    if let Some(Command::Ctl { request }) = &args.command {
+        return send_control_request(request).await;
+    }
+
+    let settings = Settings::load(args.config.clone())?;
+    if let Some(Command::Stats { log }) = args.command {
+        return print_stats(log, &settings);
+    }
+
+    let _instance = match InstanceGuard::acquire() {
+        Ok(instance) => instance,
+        Err(err) => {
+            if let Ok(Response {
+                status: Some(status),
+                ..
+            }) = control::request(&Request::Status).await
+            {
+                bail!(
+                    "pathclip is already running (pid {}), use `pathclip ctl` to control it",
+                    status.pid
+                );
+            }
+            return Err(err);
+        }
+    };
     info!("pathclip daemon started");
 
     let (sender, receiver) = unbounded_channel();
-    let clipboard_monitor = if settings.auto_profile().is_some() {
+    let clipboard_monitor = if settings.has_auto_conversion() {
         Some(ClipboardMonitor::spawn(sender.clone())?)
     } else {
         None
     };
-    let hotkey_monitor = HotkeyMonitor::spawn(settings.registered_hotkeys(), sender)?;
+    // 控制端点的名称可以被其他用户抢先占用, 创建失败时只禁用 `pathclip ctl`
+    let control_server = match ControlServer::spawn(sender.clone(), args.config) {
+        Ok(server) => Some(server),
+        Err(err) => {
+            warn!(error = ?err, "control endpoint is unavailable, `pathclip ctl` is disabled");
+            None
+        }
+    };
+    let (hotkey_monitor, hotkey_failures) =
+        HotkeyMonitor::spawn(settings.hotkey_bindings(), sender)?;
 
-    if settings.auto_profile().is_none() && hotkey_monitor.is_none() {
+    if !settings.has_auto_conversion() && hotkey_monitor.is_none() {
         warn!("automatic conversion and all hotkeys are disabled");
     }
 
-    let mut runner = Runner::new(settings, receiver)?;
+    let mut runner = Runner::new(settings, SystemClipboard::new()?, receiver)
+        .with_hotkey_failures(hotkey_failures);
     tokio::select! {
         result = runner.run() => result?,
         signal = tokio::signal::ctrl_c() => {
@@ -77,6 +144,9 @@ pub async fn run() -> Result<()> {
         }
     }
 
+    if let Some(server) = control_server {
+        server.stop();
+    }
     if let Some(monitor) = clipboard_monitor {
         monitor.stop().await;
     }
@@ -86,3 +156,32 @@ pub async fn run() -> Result<()> {
 
     Ok(())
 }
+
+async fn send_control_request(request: &Request) -> Result<()> {
+    let response = control::request(request).await?;
+    if !response.ok {
+        bail!(
+            "{}",
+            response.message.as_deref().unwrap_or("request failed")
+        );
+    }
+    if let Some(status) = response.status {
+        print!("{status}");
+    }
+    if let Some(message) = response.message {
+        println!("{message}");
+    }
+    Ok(())
+}
+
+fn print_stats(log: Option<PathBuf>, settings: &Settings) -> Result<()> {
+    let path = log
+        .or_else(|| settings.audit_log().map(PathBuf::from))
+        .context("audit_log is not configured, pass --log <PATH>")?;
+    let file = File::open(&path)
+        .with_context(|| format!("failed to open audit log: {}", path.display()))?;
+    let stats = Stats::from_reader(BufReader::new(file))
+        .with_context(|| format!("invalid audit log: {}", path.display()))?;
+    print!("{stats}");
+    Ok(())
+}
@@ -1,56 +1,184 @@
 use std::time::{Duration, Instant};
 
-use anyhow::{Result, anyhow};
+use anyhow::{Result, anyhow, bail};
 use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::time::Instant as TokioInstant;
 use tracing::{debug, info, warn};
 
 use super::RuntimeEvent;
-use super::clipboard::{ClipboardAccess, SystemClipboard};
-use super::settings::{Profile, Settings};
+use super::audit::{AuditEntry, AuditLog, Outcome, Trigger};
+use super::clipboard::{ClipboardAccess, ClipboardContents, SystemClipboard};
+use super::control::{AutoConversion, ControlCommand, Response, Status};
+use super::foreground::{ForegroundLookup, SystemForeground};
+use super::hotkey::HotkeyFailure;
+use super::keyboard::{KeyboardInput, SystemKeyboard};
+use super::settings::{Profile, Settings, TransformStep};
 use super::transform::{TransformResult, transform_files, transform_text};
 
-pub(super) struct Runner<C = SystemClipboard> {
+const TRANSFORM_DEADLINE: Duration = Duration::from_secs(30);
+
+pub(super) struct Runner<C = SystemClipboard, F = SystemForeground, K = SystemKeyboard> {
     settings: Settings,
     clipboard: C,
+    foreground: F,
+    keyboard: K,
     receiver: UnboundedReceiver<RuntimeEvent>,
     recent_write: Option<RecentWrite>,
+    auto_state: AutoState,
+    audit: Option<AuditLog>,
+    hotkey_failures: Vec<HotkeyFailure>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum AutoState {
+    Active,
+    Paused { until: Option<TokioInstant> },
 }
 
-impl Runner<SystemClipboard> {
-    pub(super) fn new(settings: Settings, receiver: UnboundedReceiver<RuntimeEvent>) -> Result<Self> {
-        Ok(Self {
+impl<C: ClipboardAccess> Runner<C, SystemForeground, SystemKeyboard> {
+    pub(super) fn new(
+        settings: Settings,
+        clipboard: C,
+        receiver: UnboundedReceiver<RuntimeEvent>,
+    ) -> Self {
+        let audit = audit_log(&settings);
+        Self {
             settings,
-            clipboard: SystemClipboard::new()?,
+            clipboard,
+            foreground: SystemForeground,
+            keyboard: SystemKeyboard,
             receiver,
             recent_write: None,
-        })
+            auto_state: AutoState::Active,
+            audit,
+            hotkey_failures: Vec::new(),
+        }
     }
 }
 
-impl<C: ClipboardAccess> Runner<C> {
+impl<C: ClipboardAccess, F: ForegroundLookup, K: KeyboardInput> Runner<C, F, K> {
+    pub(super) fn with_hotkey_failures(mut self, hotkey_failures: Vec<HotkeyFailure>) -> Self {
+        self.hotkey_failures = hotkey_failures;
+        self
+    }
+
     pub(super) async fn run(&mut self) -> Result<()> {
-        while let Some(event) = self.receiver.recv().await {
+        let mut pending_change = None;
+        loop {
+            let event = tokio::select! {
+                event = self.receiver.recv() => event,
+                () = sleep_until_debounced(pending_change) => {
+                    pending_change = None;
+                    self.handle_clipboard_change().await;
+                    continue;
+                }
+                () = sleep_until_resume(self.auto_state) => {
+                    self.resume_auto_conversion();
+                    continue;
+                }
+            };
+            let Some(event) = event else {
+                if pending_change.is_some() {
+                    self.handle_clipboard_change().await;
+                }
+                break;
+            };
+
             match event {
-                RuntimeEvent::ClipboardChanged => self.handle_clipboard_change().await,
-                RuntimeEvent::HotkeyPressed(id) => self.handle_hotkey(id).await,
+                RuntimeEvent::ClipboardChanged => {
+                    pending_change
+                        .get_or_insert_with(|| TokioInstant::now() + self.settings.debounce());
+                }
+                RuntimeEvent::HotkeyPressed(id) => {
+                    if pending_change.take().is_some() {
+                        self.handle_clipboard_change().await;
+                    }
+                    self.handle_hotkey(id).await;
+                }
+                RuntimeEvent::Control(request) => {
+                    if pending_change.take().is_some() {
+                        self.handle_clipboard_change().await;
+                    }
+                    let quit = matches!(request.command, ControlCommand::Quit);
+                    let response = self.handle_control(request.command).await;
+                    let _ = request.reply.send(response);
+                    if quit {
+                        break;
+                    }
+                }
             }
         }
         Ok(())
     }
 
-    async fn handle_clipboard_change(&mut self) {
-        let Some(profile) = self.settings.auto_profile().cloned() else {
-            return;
+    #[cfg(test)]
+    pub(super) fn auto_state(&self) -> AutoState {
+        self.auto_state
+    }
+
+    fn toggle_auto_conversion(&mut self) {
+        match self.auto_state {
+            AutoState::Active => self.pause_auto_conversion(self.settings.suspend_for()),
+            AutoState::Paused { .. } => self.resume_auto_conversion(),
+        }
+    }
+
+    fn pause_auto_conversion(&mut self, duration: Option<Duration>) {
+        self.auto_state = AutoState::Paused {
+            // 超出时钟范围的时长按直到手动恢复处理
+            until: duration.and_then(|duration| TokioInstant::now().checked_add(duration)),
         };
+        self.recent_write = None;
+        match duration {
+            Some(duration) => info!(resume_after = ?duration, "automatic conversion paused"),
+            None => info!("automatic conversion paused until resumed"),
+        }
+    }
 
-        if self.clipboard.has_files() {
-            self.recent_write = None;
-            debug!(profile = %profile.name, "automatic conversion skipped for file clipboard");
+    fn resume_auto_conversion(&mut self) {
+        if self.auto_state != AutoState::Active {
+            self.auto_state = AutoState::Active;
+            info!("automatic conversion resumed");
+        }
+    }
+
+    async fn handle_clipboard_change(&mut self) {
+        if !self.settings.has_auto_conversion() {
             return;
         }
-        if !self.clipboard.has_text() {
-            self.recent_write = None;
+        if let AutoState::Paused { .. } = self.auto_state {
+            debug!("automatic conversion is paused");
+            return;
+        }
+        let app = if self.settings.has_auto_rules() {
+            self.foreground.current()
+        } else {
+            None
+        };
+        let Some(profile) = self.settings.auto_profile_for(app.as_ref()).cloned() else {
+            debug!(
+                process = app.as_ref().map(|app| app.process.as_str()),
+                "automatic conversion is disabled for the foreground application"
+            );
             return;
+        };
+
+        match self.content_kind() {
+            ContentKind::Private => {
+                self.recent_write = None;
+                debug!(profile = %profile.name, "automatic conversion skipped for private clipboard content");
+                return;
+            }
+            ContentKind::Files => {
+                self.recent_write = None;
+                debug!(profile = %profile.name, "automatic conversion skipped for file clipboard");
+                return;
+            }
+            ContentKind::Empty => {
+                self.recent_write = None;
+                return;
+            }
+            ContentKind::Text => {}
         }
 
         let text = match self.read_text_with_retry().await {
@@ -64,53 +192,347 @@ impl<C: ClipboardAccess> Runner<C> {
         if self.is_recent_self_write(&text) {
             return;
         }
+        if let Err(err) = self.settings.check_input_size(&text) {
+            debug!(profile = %profile.name, reason = %err, "automatic conversion skipped for large clipboard text");
+            return;
+        }
 
-        match transform_text(&profile, &text) {
+        let started = Instant::now();
+        let input = text.clone();
+        match run_transform(&profile, move |profile| transform_text(profile, &input)).await {
             Ok(Some(result)) if result.output == text => {
                 debug!(profile = %profile.name, "conversion output is unchanged");
+                self.record(
+                    AuditEntry::new(Trigger::Auto, &profile.name, started, Outcome::Unchanged)
+                        .with_paths(result.path_count)
+                        .with_contents(&text, Some(&result.output)),
+                )
+                .await;
+            }
+            Ok(Some(result)) => {
+                let (path_count, output) = (result.path_count, result.output.clone());
+                let written = self.write_result(&profile, result, Vec::new()).await;
+                self.record(
+                    AuditEntry::new(
+                        Trigger::Auto,
+                        &profile.name,
+                        started,
+                        written_outcome(written),
+                    )
+                    .with_paths(path_count)
+                    .with_contents(&text, Some(&output)),
+                )
+                .await;
+            }
+            Ok(None) => {
+                debug!(profile = %profile.name, "clipboard text is not an absolute Windows path list")
+            }
+            Err(err) => {
+                warn!(profile = %profile.name, error = ?err, "automatic path conversion failed");
+                self.record(
+                    AuditEntry::new(Trigger::Auto, &profile.name, started, Outcome::Failed)
+                        .with_error(&err)
+                        .with_contents(&text, None),
+                )
+                .await;
             }
-            Ok(Some(result)) => self.write_result(&profile, result).await,
-            Ok(None) => debug!(profile = %profile.name, "clipboard text is not an absolute Windows path list"),
-            Err(err) => warn!(profile = %profile.name, error = ?err, "automatic path conversion failed"),
         }
     }
 
     async fn handle_hotkey(&mut self, hotkey_id: u32) {
+        if self.settings.is_toggle_hotkey(hotkey_id) {
+            self.toggle_auto_conversion();
+            return;
+        }
+
         let Some(profile) = self.settings.profile_for_hotkey(hotkey_id).cloned() else {
             warn!(hotkey_id, "received an unknown hotkey event");
             return;
         };
 
-        let result = if self.clipboard.has_files() {
-            match self.read_files_with_retry().await {
-                Ok(paths) => transform_files(&profile, &paths),
-                Err(err) => Err(err),
+        // 粘贴热键按粘贴目标匹配 auto_rules, 粘贴和恢复剪贴板仍按热键 profile 的设置
+        let profile = if profile.paste && self.settings.has_auto_rules() {
+            let app = self.foreground.current();
+            let target = self.settings.paste_profile_for(&profile, app.as_ref());
+            debug!(
+                hotkey_profile = %profile.name,
+                profile = %target.name,
+                process = app.as_ref().map(|app| app.process.as_str()),
+                "paste profile selected for the foreground application"
+            );
+            Profile {
+                paste: true,
+                restore_delay: profile.restore_delay,
+                ..target.clone()
             }
-        } else if self.clipboard.has_text() {
-            match self.read_text_with_retry().await {
-                Ok(text) => match transform_text(&profile, &text) {
-                    Ok(Some(result)) if result.output == text => {
-                        debug!(profile = %profile.name, "conversion output is unchanged");
-                        return;
-                    }
-                    Ok(Some(result)) => Ok(result),
-                    Ok(None) => Err(anyhow!(
-                        "clipboard text is not an absolute Windows path list"
-                    )),
-                    Err(err) => Err(err),
-                },
+        } else {
+            profile
+        };
+
+        let trigger = Trigger::Hotkey { id: hotkey_id };
+        let _ = self
+            .convert_clipboard(&profile, trigger, profile.paste)
+            .await;
+    }
+
+    async fn handle_control(&mut self, command: ControlCommand) -> Response {
+        match command {
+            ControlCommand::Status => Response::status(self.status()),
+            ControlCommand::Pause(duration) => {
+                self.pause_auto_conversion(duration.or(self.settings.suspend_for()));
+                Response::ok("automatic conversion paused")
+            }
+            ControlCommand::Resume => {
+                self.resume_auto_conversion();
+                Response::ok("automatic conversion resumed")
+            }
+            ControlCommand::Reload(settings) => self.reload_settings(*settings),
+            ControlCommand::Convert(name) => {
+                let Some(profile) = self.settings.profile(&name).cloned() else {
+                    return Response::error(format!("unknown profile `{name}`"));
+                };
+                match self
+                    .convert_clipboard(&profile, Trigger::Control, false)
+                    .await
+                {
+                    Ok(message) => Response::ok(message),
+                    Err(err) => Response::error(format!("{err:#}")),
+                }
+            }
+            ControlCommand::Quit => {
+                info!("shutdown requested by control client");
+                Response::ok("pathclip is shutting down")
+            }
+        }
+    }
+
+    fn status(&self) -> Status {
+        let (auto_conversion, resume_in_ms) = match self.auto_state {
+            _ if !self.settings.has_auto_conversion() => (AutoConversion::Disabled, None),
+            AutoState::Active => (AutoConversion::Active, None),
+            AutoState::Paused { until } => (
+                AutoConversion::Paused,
+                until.map(|until| {
+                    until
+                        .saturating_duration_since(TokioInstant::now())
+                        .as_millis() as u64
+                }),
+            ),
+        };
+        Status {
+            pid: std::process::id(),
+            auto_conversion,
+            resume_in_ms,
+            auto_profile: self
+                .settings
+                .auto_profile()
+                .map(|profile| profile.name.clone()),
+            profiles: self.settings.profile_names(),
+            hotkey_failures: self.hotkey_failures.clone(),
+        }
+    }
+
+    fn reload_settings(&mut self, settings: Settings) -> Response {
+        let hotkey_ids = |settings: &Settings| {
+            settings
+                .registered_hotkeys()
+                .iter()
+                .map(|hotkey| hotkey.id())
+                .collect::<Vec<_>>()
+        };
+        let needs_restart = hotkey_ids(&settings) != hotkey_ids(&self.settings)
+            || settings.has_auto_conversion() != self.settings.has_auto_conversion();
+
+        self.audit = audit_log(&settings);
+        self.settings = settings;
+        info!("settings reloaded");
+        if needs_restart {
+            warn!("hotkey or clipboard monitoring changes take effect after restart");
+            Response::ok(
+                "settings reloaded, hotkey or clipboard monitoring changes take effect after restart",
+            )
+        } else {
+            Response::ok("settings reloaded")
+        }
+    }
+
+    async fn convert_clipboard(
+        &mut self,
+        profile: &Profile,
+        trigger: Trigger,
+        paste: bool,
+    ) -> Result<String> {
+        let started = Instant::now();
+        let mut original = ClipboardContents::default();
+        let result = match self.content_kind() {
+            ContentKind::Private => Err(anyhow!("clipboard content is marked private")),
+            ContentKind::Files => match self.read_files_with_retry().await {
+                Ok(paths) => {
+                    let input = paths.clone();
+                    let result =
+                        run_transform(profile, move |profile| transform_files(profile, &input))
+                            .await;
+                    original.files = paths;
+                    result
+                }
                 Err(err) => Err(err),
+            },
+            ContentKind::Text => {
+                match self.read_text_with_retry().await.and_then(|text| {
+                    self.settings.check_input_size(&text)?;
+                    Ok(text)
+                }) {
+                    Ok(text) => match run_transform(profile, {
+                        let text = text.clone();
+                        move |profile| transform_text(profile, &text)
+                    })
+                    .await
+                    {
+                        Ok(Some(result)) if result.output == text => {
+                            debug!(profile = %profile.name, "conversion output is unchanged");
+                            self.record(
+                                AuditEntry::new(
+                                    trigger,
+                                    &profile.name,
+                                    started,
+                                    Outcome::Unchanged,
+                                )
+                                .with_paths(result.path_count)
+                                .with_contents(&text, Some(&result.output)),
+                            )
+                            .await;
+                            if paste && !self.inject_paste(profile).await {
+                                return Ok("clipboard paths are already converted, paste skipped"
+                                    .to_string());
+                            }
+                            return Ok("clipboard paths are already converted".to_string());
+                        }
+                        Ok(Some(result)) => {
+                            original.text = text;
+                            Ok(result)
+                        }
+                        Ok(None) => Err(anyhow!(
+                            "clipboard text is not an absolute Windows path list"
+                        )),
+                        Err(err) => Err(err),
+                    },
+                    Err(err) => Err(err),
+                }
+            }
+            ContentKind::Empty => Err(anyhow!("clipboard does not contain text or files")),
+        };
+
+        let result = match result {
+            Ok(result) => result,
+            Err(err) => {
+                warn!(profile = %profile.name, error = ?err, "path conversion failed");
+                self.record(
+                    AuditEntry::new(trigger, &profile.name, started, Outcome::Failed)
+                        .with_error(&err),
+                )
+                .await;
+                return Err(err);
             }
+        };
+
+        let files = if profile.keep_files {
+            original.files.clone()
+        } else {
+            Vec::new()
+        };
+        let input = if original.files.is_empty() {
+            original.text.clone()
         } else {
-            Err(anyhow!("clipboard does not contain text or files"))
+            original.files.join("\r\n")
         };
+        let (path_count, output) = (result.path_count, result.output.clone());
+        let written = self.write_result(profile, result, files).await;
+        self.record(
+            AuditEntry::new(trigger, &profile.name, started, written_outcome(written))
+                .with_paths(path_count)
+                .with_contents(&input, Some(&output)),
+        )
+        .await;
+        if !written {
+            bail!("failed to write converted clipboard text");
+        }
+
+        if paste {
+            let pasted = self.inject_paste(profile).await;
+            if pasted {
+                tokio::time::sleep(profile.restore_delay).await;
+            }
+            self.restore_clipboard(profile, original).await;
+            if !pasted {
+                return Ok(format!(
+                    "converted {path_count} paths with profile `{}`, paste skipped",
+                    profile.name
+                ));
+            }
+        }
+        Ok(format!(
+            "converted {path_count} paths with profile `{}`",
+            profile.name
+        ))
+    }
+
+    /// 等待热键的修饰键松开后模拟 Ctrl+V, 返回是否已经粘贴
+    async fn inject_paste(&self, profile: &Profile) -> bool {
+        let mut released = self.keyboard.modifiers_released();
+        for _ in 0..50 {
+            if released {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            released = self.keyboard.modifiers_released();
+        }
+        // 修饰键仍然按下时粘贴会变成 Ctrl+Shift+V 等组合键, 可能再次触发热键
+        if !released {
+            warn!(profile = %profile.name, "modifier keys are still held, paste skipped");
+            return false;
+        }
 
-        match result {
-            Ok(result) => self.write_result(&profile, result).await,
-            Err(err) => warn!(profile = %profile.name, error = ?err, "hotkey path conversion failed"),
+        match self.keyboard.send_paste() {
+            Ok(()) => true,
+            Err(err) => {
+                warn!(profile = %profile.name, error = ?err, "failed to paste converted clipboard text");
+                false
+            }
+        }
+    }
+
+    async fn restore_clipboard(&mut self, profile: &Profile, original: ClipboardContents) {
+        match retry_clipboard(|| self.clipboard.set_contents(original.clone())).await {
+            Ok(()) => {
+                self.recent_write = (!original.text.is_empty()).then(|| RecentWrite {
+                    text: original.text,
+                    at: Instant::now(),
+                    remaining: 2,
+                });
+                debug!(profile = %profile.name, "original clipboard content restored");
+            }
+            Err(err) => {
+                warn!(profile = %profile.name, error = ?err, "failed to restore original clipboard content")
+            }
         }
     }
 
+    /// 只列出一次格式判断剪贴板内容, 之后的读取不再使用这份列表
+    fn content_kind(&self) -> ContentKind {
+        self.clipboard.refresh_formats();
+        let kind = if self.clipboard.is_private() {
+            ContentKind::Private
+        } else if self.clipboard.has_files() {
+            ContentKind::Files
+        } else if self.clipboard.has_text() {
+            ContentKind::Text
+        } else {
+            ContentKind::Empty
+        };
+        self.clipboard.forget_formats();
+        kind
+    }
+
     async fn read_text_with_retry(&self) -> Result<String> {
         retry_clipboard(|| self.clipboard.get_text()).await
     }
@@ -119,26 +541,59 @@ impl<C: ClipboardAccess> Runner<C> {
         retry_clipboard(|| self.clipboard.get_files()).await
     }
 
-    async fn write_result(&mut self, profile: &Profile, result: TransformResult) {
+    async fn write_result(
+        &mut self,
+        profile: &Profile,
+        result: TransformResult,
+        files: Vec<String>,
+    ) -> bool {
         if result.output.is_empty() {
             warn!(profile = %profile.name, "conversion produced empty clipboard text");
-            return;
+            return false;
         }
 
-        match retry_clipboard(|| self.clipboard.set_text(result.output.clone())).await {
+        let written = if files.is_empty() {
+            retry_clipboard(|| self.clipboard.set_text(result.output.clone())).await
+        } else {
+            let contents = ClipboardContents::text_with_files(result.output.clone(), files);
+            retry_clipboard(|| self.clipboard.set_contents(contents.clone())).await
+        };
+        match written {
             Ok(()) => {
                 self.recent_write = Some(RecentWrite {
                     text: result.output,
                     at: Instant::now(),
+                    remaining: 1,
                 });
-                info!(profile = %profile.name, paths = result.path_count, "clipboard paths converted");
+                info!(
+                    profile = %profile.name,
+                    paths = result.path_count,
+                    skipped = result.skipped,
+                    "clipboard paths converted"
+                );
+                true
+            }
+            Err(err) => {
+                warn!(profile = %profile.name, error = ?err, "failed to write converted clipboard text");
+                false
             }
-            Err(err) => warn!(profile = %profile.name, error = ?err, "failed to write converted clipboard text"),
+        }
+    }
+
+    /// 在阻塞线程池中追加审计日志, 文件 I/O 不会卡住 runner
+    async fn record(&self, entry: AuditEntry) {
+        let Some(audit) = self.audit.clone() else {
+            return;
+        };
+        match tokio::task::spawn_blocking(move || audit.append(entry)).await {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => warn!(error = ?err, "failed to write audit log entry"),
+            Err(err) => warn!(error = ?err, "audit log writer task failed"),
         }
     }
 
     fn is_recent_self_write(&mut self, text: &str) -> bool {
-        let Some(recent) = &self.recent_write else {
+        let Some(recent) = &mut self.recent_write else {
             return false;
         };
 
@@ -148,7 +603,10 @@ impl<C: ClipboardAccess> Runner<C> {
         }
 
         if recent.text == text {
-            self.recent_write = None;
+            recent.remaining -= 1;
+            if recent.remaining == 0 {
+                self.recent_write = None;
+            }
             debug!("ignored clipboard notification caused by pathclip");
             return true;
         }
@@ -157,9 +615,46 @@ impl<C: ClipboardAccess> Runner<C> {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ContentKind {
+    Private,
+    Files,
+    Text,
+    Empty,
+}
+
 struct RecentWrite {
     text: String,
     at: Instant,
+    remaining: usize,
+}
+
+fn audit_log(settings: &Settings) -> Option<AuditLog> {
+    settings
+        .audit_log()
+        .map(|path| AuditLog::new(path.to_path_buf(), settings.log_contents()))
+}
+
+fn written_outcome(written: bool) -> Outcome {
+    if written {
+        Outcome::Converted
+    } else {
+        Outcome::Failed
+    }
+}
+
+async fn sleep_until_debounced(deadline: Option<TokioInstant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn sleep_until_resume(state: AutoState) {
+    match state {
+        AutoState::Paused { until: Some(until) } => tokio::time::sleep_until(until).await,
+        _ => std::future::pending().await,
+    }
 }
 
 async fn retry_clipboard<T, F>(mut operation: F) -> Result<T>
@@ -181,19 +676,65 @@ where
     Err(last_error.expect("clipboard retry loop must have an error"))
 }
 
+/// 在阻塞线程池中执行转换, exec 步骤不会卡住 runner
+async fn run_transform<T, F>(profile: &Profile, transform: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&Profile) -> Result<T> + Send + 'static,
+{
+    let deadline = transform_deadline(profile);
+    let profile = profile.clone();
+    match tokio::time::timeout(
+        deadline,
+        tokio::task::spawn_blocking(move || transform(&profile)),
+    )
+    .await
+    {
+        Ok(Ok(result)) => result,
+        Ok(Err(err)) => Err(anyhow!("conversion task failed: {err}")),
+        Err(_) => bail!("conversion did not finish within {deadline:?}"),
+    }
+}
+
+/// exec 步骤对每个路径各运行一次, 整个转换另设时间上限, 不短于最长的 exec 超时
+fn transform_deadline(profile: &Profile) -> Duration {
+    profile
+        .steps
+        .iter()
+        .filter_map(|step| match step {
+            TransformStep::Exec(command) => Some(command.timeout),
+            _ => None,
+        })
+        .fold(TRANSFORM_DEADLINE, Duration::max)
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
     use std::sync::Mutex;
 
-    use anyhow::Result;
+    use anyhow::{Result, anyhow};
     use tokio::sync::mpsc::unbounded_channel;
 
-    use super::{ClipboardAccess, RecentWrite, Runner, Settings};
+    use std::time::Duration;
+
+    use super::{
+        AutoState, ClipboardAccess, ClipboardContents, ForegroundLookup, KeyboardInput,
+        RecentWrite, Runner, Settings,
+    };
+    use crate::app::RuntimeEvent;
+    use crate::app::audit::{AuditEntry, AuditLog, Outcome, Trigger};
+    use crate::app::control::{AutoConversion, ControlCommand, ControlRequest, Response};
+    use crate::app::foreground::ForegroundApp;
+    use crate::app::hotkey::HotkeyFailure;
 
     #[derive(Default)]
     struct MockState {
         files: Vec<String>,
         text: String,
+        formats: HashMap<String, Vec<u8>>,
+        html: Option<String>,
+        reads: usize,
         writes: Vec<String>,
     }
 
@@ -221,6 +762,19 @@ mod tests {
             }
         }
 
+        fn with_format(self, name: &str, data: &[u8]) -> Self {
+            self.state
+                .lock()
+                .unwrap()
+                .formats
+                .insert(name.to_string(), data.to_vec());
+            self
+        }
+
+        fn reads(&self) -> usize {
+            self.state.lock().unwrap().reads
+        }
+
         fn writes(&self) -> Vec<String> {
             self.state.lock().unwrap().writes.clone()
         }
@@ -235,34 +789,111 @@ mod tests {
             !self.state.lock().unwrap().text.is_empty()
         }
 
+        fn has_format(&self, name: &str) -> bool {
+            self.state.lock().unwrap().formats.contains_key(name)
+        }
+
         fn get_files(&self) -> Result<Vec<String>> {
-            Ok(self.state.lock().unwrap().files.clone())
+            let mut state = self.state.lock().unwrap();
+            state.reads += 1;
+            Ok(state.files.clone())
         }
 
         fn get_text(&self) -> Result<String> {
-            Ok(self.state.lock().unwrap().text.clone())
+            let mut state = self.state.lock().unwrap();
+            state.reads += 1;
+            Ok(state.text.clone())
+        }
+
+        fn get_buffer(&self, name: &str) -> Result<Vec<u8>> {
+            self.state
+                .lock()
+                .unwrap()
+                .formats
+                .get(name)
+                .cloned()
+                .ok_or_else(|| anyhow!("format `{name}` is not available"))
         }
 
         fn set_text(&self, text: String) -> Result<()> {
             let mut state = self.state.lock().unwrap();
             state.text.clone_from(&text);
             state.files.clear();
+            state.formats.clear();
+            state.html = None;
             state.writes.push(text);
             Ok(())
         }
+
+        fn set_contents(&self, contents: ClipboardContents) -> Result<()> {
+            let mut state = self.state.lock().unwrap();
+            state.text.clone_from(&contents.text);
+            state.files = contents.files;
+            state.formats.clear();
+            state.html = contents.html;
+            state.writes.push(contents.text);
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockForeground {
+        app: Option<ForegroundApp>,
+    }
+
+    impl MockForeground {
+        fn with_process(process: &str) -> Self {
+            Self {
+                app: Some(ForegroundApp {
+                    process: process.to_string(),
+                    window_title: String::new(),
+                }),
+            }
+        }
+    }
+
+    impl ForegroundLookup for MockForeground {
+        fn current(&self) -> Option<ForegroundApp> {
+            self.app.clone()
+        }
+    }
+
+    #[derive(Default)]
+    struct MockKeyboard {
+        pastes: Mutex<usize>,
+        held: bool,
+    }
+
+    impl KeyboardInput for MockKeyboard {
+        fn modifiers_released(&self) -> bool {
+            !self.held
+        }
+
+        fn send_paste(&self) -> Result<()> {
+            *self.pastes.lock().unwrap() += 1;
+            Ok(())
+        }
     }
 
     fn settings(source: &str) -> Settings {
         Settings::parse(source).unwrap()
     }
 
-    fn runner(settings: Settings, clipboard: MockClipboard) -> Runner<MockClipboard> {
+    fn runner(
+        settings: Settings,
+        clipboard: MockClipboard,
+    ) -> Runner<MockClipboard, MockForeground, MockKeyboard> {
         let (_sender, receiver) = unbounded_channel();
         Runner {
             settings,
             clipboard,
+            foreground: MockForeground::default(),
+            keyboard: MockKeyboard::default(),
             receiver,
             recent_write: None,
+            auto_state: AutoState::Active,
+            audit: None,
+            hotkey_failures: Vec::new(),
         }
     }
 
@@ -306,42 +937,718 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn partial_file_conversion_failure_keeps_clipboard() {
+    async fn hotkey_keeps_file_objects_alongside_converted_text() {
         let settings = settings(
             r#"
                 auto_profile = ""
 
                 [profiles.wsl]
                 hotkey = "Ctrl+Shift+V"
+                keep_files = true
                 steps = [{ type = "wsl" }]
             "#,
         );
         let hotkey_id = settings.registered_hotkeys()[0].id();
         let mut runner = runner(
             settings,
-            MockClipboard::with_files(&[r"C:\a.txt", r"\\server\share\b.txt"]),
+            MockClipboard::with_files(&[r"C:\a.txt", r"D:\b&c.txt"]),
         );
 
         runner.handle_hotkey(hotkey_id).await;
 
-        assert!(runner.clipboard.writes().is_empty());
+        let state = runner.clipboard.state.lock().unwrap();
+        assert_eq!(state.writes, vec!["/mnt/c/a.txt\r\n/mnt/d/b&c.txt"]);
+        assert_eq!(state.files, vec![r"C:\a.txt", r"D:\b&c.txt"]);
+        assert_eq!(
+            state.html.as_deref(),
+            Some("/mnt/c/a.txt<br>/mnt/d/b&amp;c.txt")
+        );
     }
 
-    #[tokio::test]
-    async fn unchanged_automatic_output_is_not_written() {
+    #[tokio::test(start_paused = true)]
+    async fn paste_hotkey_pastes_converted_text_and_restores_original() {
         let settings = settings(
             r#"
                 auto_profile = "slash"
 
+                [profiles.wsl]
+                hotkey = "Ctrl+Shift+V"
+                paste = true
+                restore_delay = "300ms"
+                steps = [{ type = "wsl" }]
+
                 [profiles.slash]
                 steps = [{ type = "forward-slash" }]
             "#,
         );
-        let mut runner = runner(settings, MockClipboard::with_text("C:/a.txt"));
+        let hotkey_id = settings.registered_hotkeys()[0].id();
+        let mut runner = runner(settings, MockClipboard::with_text(r"C:\a.txt"));
+
+        let started = tokio::time::Instant::now();
+        runner.handle_hotkey(hotkey_id).await;
+
+        assert!(started.elapsed() >= Duration::from_millis(300));
+        assert_eq!(*runner.keyboard.pastes.lock().unwrap(), 1);
+        assert_eq!(runner.clipboard.writes(), vec!["/mnt/c/a.txt", r"C:\a.txt"]);
 
         runner.handle_clipboard_change().await;
+        runner.handle_clipboard_change().await;
+        assert_eq!(runner.clipboard.writes().len(), 2);
+    }
 
+    #[tokio::test(start_paused = true)]
+    async fn paste_hotkey_uses_rule_profile_of_the_paste_target() {
+        let settings = settings(
+            r#"
+                auto_profile = ""
+
+                [[auto_rules]]
+                process = "WindowsTerminal.exe"
+                profile = "wsl"
+
+                [profiles.paste]
+                hotkey = "Ctrl+Shift+V"
+                paste = true
+                restore_delay = "300ms"
+                steps = [{ type = "forward-slash" }]
+
+                [profiles.wsl]
+                steps = [{ type = "wsl" }]
+            "#,
+        );
+        let hotkey_id = settings.registered_hotkeys()[0].id();
+        let mut runner = runner(settings, MockClipboard::with_text(r"C:\a.txt"));
+
+        // 在资源管理器中复制, 粘贴到终端时才选择 wsl
+        runner.handle_clipboard_change().await;
         assert!(runner.clipboard.writes().is_empty());
+        runner.foreground = MockForeground::with_process("WindowsTerminal.exe");
+
+        let started = tokio::time::Instant::now();
+        runner.handle_hotkey(hotkey_id).await;
+
+        assert!(started.elapsed() >= Duration::from_millis(300));
+        assert_eq!(*runner.keyboard.pastes.lock().unwrap(), 1);
+        assert_eq!(runner.clipboard.writes(), vec!["/mnt/c/a.txt", r"C:\a.txt"]);
+
+        runner.foreground = MockForeground::with_process("notepad.exe");
+        runner.handle_hotkey(hotkey_id).await;
+        assert_eq!(runner.clipboard.writes()[2], "C:/a.txt");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn paste_hotkey_skips_paste_while_modifiers_are_held() {
+        let settings = settings(
+            r#"
+                auto_profile = ""
+
+                [profiles.wsl]
+                hotkey = "Ctrl+Shift+V"
+                paste = true
+                restore_delay = "300ms"
+                steps = [{ type = "wsl" }]
+            "#,
+        );
+        let hotkey_id = settings.registered_hotkeys()[0].id();
+        let mut runner = runner(settings, MockClipboard::with_text(r"C:\a.txt"));
+        runner.keyboard.held = true;
+
+        let started = tokio::time::Instant::now();
+        runner.handle_hotkey(hotkey_id).await;
+
+        assert!(started.elapsed() >= Duration::from_secs(1));
+        assert!(started.elapsed() < Duration::from_millis(1300));
+        assert_eq!(*runner.keyboard.pastes.lock().unwrap(), 0);
+        assert_eq!(runner.clipboard.writes(), vec!["/mnt/c/a.txt", r"C:\a.txt"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn paste_hotkey_restores_file_objects() {
+        let settings = settings(
+            r#"
+                auto_profile = ""
+
+                [profiles.wsl]
+                hotkey = "Ctrl+Shift+V"
+                paste = true
+                steps = [{ type = "wsl" }]
+            "#,
+        );
+        let hotkey_id = settings.registered_hotkeys()[0].id();
+        let mut runner = runner(settings, MockClipboard::with_files(&[r"C:\a.txt"]));
+
+        runner.handle_hotkey(hotkey_id).await;
+
+        let state = runner.clipboard.state.lock().unwrap();
+        assert_eq!(state.writes, vec!["/mnt/c/a.txt", ""]);
+        assert_eq!(state.files, vec![r"C:\a.txt"]);
+        assert!(state.text.is_empty());
+    }
+
+    #[tokio::test]
+    async fn partial_file_conversion_failure_keeps_clipboard() {
+        let settings = settings(
+            r#"
+                auto_profile = ""
+
+                [profiles.wsl]
+                hotkey = "Ctrl+Shift+V"
+                steps = [{ type = "wsl" }]
+            "#,
+        );
+        let hotkey_id = settings.registered_hotkeys()[0].id();
+        let mut runner = runner(
+            settings,
+            MockClipboard::with_files(&[r"C:\a.txt", r"\\server\share\b.txt"]),
+        );
+
+        runner.handle_hotkey(hotkey_id).await;
+
+        assert!(runner.clipboard.writes().is_empty());
+    }
+
+    #[tokio::test]
+    async fn skip_line_profile_converts_mixed_clipboard_text() {
+        let settings = settings(
+            r#"
+                auto_profile = "slash"
+
+                [profiles.slash]
+                on_mixed = "skip-line"
+                steps = [{ type = "forward-slash" }]
+            "#,
+        );
+        let mut runner = runner(settings, MockClipboard::with_text("see C:\\x\r\nC:\\a.txt"));
+
+        runner.handle_clipboard_change().await;
+
+        assert_eq!(runner.clipboard.writes(), vec!["see C:\\x\r\nC:/a.txt"]);
+    }
+
+    #[tokio::test]
+    async fn unchanged_automatic_output_is_not_written() {
+        let settings = settings(
+            r#"
+                auto_profile = "slash"
+
+                [profiles.slash]
+                steps = [{ type = "forward-slash" }]
+            "#,
+        );
+        let mut runner = runner(settings, MockClipboard::with_text("C:/a.txt"));
+
+        runner.handle_clipboard_change().await;
+
+        assert!(runner.clipboard.writes().is_empty());
+    }
+
+    #[tokio::test]
+    async fn automatic_conversion_uses_foreground_rule_profile() {
+        let settings = settings(
+            r#"
+                auto_profile = "slash"
+
+                [[auto_rules]]
+                process = "WindowsTerminal.exe"
+                profile = "wsl"
+
+                [profiles.slash]
+                steps = [{ type = "forward-slash" }]
+
+                [profiles.wsl]
+                steps = [{ type = "wsl" }]
+            "#,
+        );
+        let mut runner = runner(settings, MockClipboard::with_text(r"C:\a.txt"));
+        runner.foreground = MockForeground::with_process("WindowsTerminal.exe");
+
+        runner.handle_clipboard_change().await;
+
+        assert_eq!(runner.clipboard.writes(), vec!["/mnt/c/a.txt"]);
+    }
+
+    #[tokio::test]
+    async fn automatic_conversion_falls_back_to_auto_profile() {
+        let settings = settings(
+            r#"
+                auto_profile = "slash"
+
+                [[auto_rules]]
+                process = "WindowsTerminal.exe"
+                profile = "wsl"
+
+                [profiles.slash]
+                steps = [{ type = "forward-slash" }]
+
+                [profiles.wsl]
+                steps = [{ type = "wsl" }]
+            "#,
+        );
+        let mut runner = runner(settings, MockClipboard::with_text(r"C:\a.txt"));
+        runner.foreground = MockForeground::with_process("explorer.exe");
+
+        runner.handle_clipboard_change().await;
+
+        assert_eq!(runner.clipboard.writes(), vec!["C:/a.txt"]);
+    }
+
+    #[tokio::test]
+    async fn toggle_hotkey_pauses_and_resumes_automatic_conversion() {
+        let settings = settings(
+            r#"
+                auto_profile = "slash"
+                toggle_hotkey = "Ctrl+Alt+P"
+
+                [profiles.slash]
+                steps = [{ type = "forward-slash" }]
+            "#,
+        );
+        let toggle_id = settings.registered_hotkeys()[0].id();
+        let mut runner = runner(settings, MockClipboard::with_text(r"C:\a.txt"));
+
+        runner.handle_hotkey(toggle_id).await;
+        assert_eq!(runner.auto_state(), AutoState::Paused { until: None });
+        runner.handle_clipboard_change().await;
+        assert!(runner.clipboard.writes().is_empty());
+
+        runner.handle_hotkey(toggle_id).await;
+        assert_eq!(runner.auto_state(), AutoState::Active);
+        runner.handle_clipboard_change().await;
+        assert_eq!(runner.clipboard.writes(), vec!["C:/a.txt"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn suspension_expires_automatically() {
+        let settings = settings(
+            r#"
+                auto_profile = "slash"
+                toggle_hotkey = "Ctrl+Alt+P"
+                suspend_for = "5m"
+
+                [profiles.slash]
+                steps = [{ type = "forward-slash" }]
+            "#,
+        );
+        let toggle_id = settings.registered_hotkeys()[0].id();
+        let (sender, receiver) = unbounded_channel();
+        let mut runner = runner(settings, MockClipboard::with_text(r"C:\a.txt"));
+        runner.receiver = receiver;
+
+        sender.send(RuntimeEvent::HotkeyPressed(toggle_id)).unwrap();
+        let handle = tokio::spawn(async move {
+            runner.run().await.unwrap();
+            runner
+        });
+
+        tokio::time::sleep(Duration::from_secs(299)).await;
+        sender.send(RuntimeEvent::ClipboardChanged).unwrap();
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        sender.send(RuntimeEvent::ClipboardChanged).unwrap();
+        tokio::time::sleep(Duration::from_millis(1)).await;
+        drop(sender);
+
+        let runner = handle.await.unwrap();
+        assert_eq!(runner.auto_state(), AutoState::Active);
+        assert_eq!(runner.clipboard.writes(), vec!["C:/a.txt"]);
+    }
+
+    #[tokio::test]
+    async fn private_clipboard_content_is_never_read() {
+        let source = r#"
+            auto_profile = "slash"
+
+            [profiles.slash]
+            hotkey = "Ctrl+Shift+V"
+            steps = [{ type = "forward-slash" }]
+        "#;
+
+        for format in [
+            "ExcludeClipboardContentFromMonitorProcessing",
+            "Clipboard Viewer Ignore",
+        ] {
+            let settings = settings(source);
+            let hotkey_id = settings.registered_hotkeys()[0].id();
+            let clipboard = MockClipboard::with_text(r"C:\secret").with_format(format, &[]);
+            let mut runner = runner(settings, clipboard);
+
+            runner.handle_clipboard_change().await;
+            runner.handle_hotkey(hotkey_id).await;
+
+            assert_eq!(runner.clipboard.reads(), 0, "{format}");
+            assert!(runner.clipboard.writes().is_empty(), "{format}");
+        }
+    }
+
+    #[tokio::test]
+    async fn clipboard_history_opt_out_is_respected() {
+        let source = r#"
+            auto_profile = "slash"
+
+            [profiles.slash]
+            steps = [{ type = "forward-slash" }]
+        "#;
+        let excluded = MockClipboard::with_text(r"C:\secret")
+            .with_format("CanIncludeInClipboardHistory", &0u32.to_le_bytes());
+        let mut runner_excluded = runner(settings(source), excluded);
+        runner_excluded.handle_clipboard_change().await;
+        assert_eq!(runner_excluded.clipboard.reads(), 0);
+
+        let allowed = MockClipboard::with_text(r"C:\a.txt")
+            .with_format("CanIncludeInClipboardHistory", &1u32.to_le_bytes());
+        let mut runner_allowed = runner(settings(source), allowed);
+        runner_allowed.handle_clipboard_change().await;
+        assert_eq!(runner_allowed.clipboard.writes(), vec!["C:/a.txt"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn clipboard_notification_burst_is_read_once() {
+        let settings = settings(
+            r#"
+                auto_profile = "slash"
+                debounce = "100ms"
+
+                [profiles.slash]
+                steps = [{ type = "forward-slash" }]
+            "#,
+        );
+        let (sender, receiver) = unbounded_channel();
+        let mut runner = runner(settings, MockClipboard::with_text(r"C:\a.txt"));
+        runner.receiver = receiver;
+        let handle = tokio::spawn(async move {
+            runner.run().await.unwrap();
+            runner
+        });
+
+        for _ in 0..4 {
+            sender.send(RuntimeEvent::ClipboardChanged).unwrap();
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        sender.send(RuntimeEvent::ClipboardChanged).unwrap();
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        drop(sender);
+
+        let runner = handle.await.unwrap();
+        assert_eq!(runner.clipboard.reads(), 2);
+        assert_eq!(runner.clipboard.writes(), vec!["C:/a.txt"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn pending_change_is_handled_before_hotkey() {
+        let settings = settings(
+            r#"
+                auto_profile = "slash"
+                debounce = "1s"
+
+                [profiles.slash]
+                steps = [{ type = "forward-slash" }]
+
+                [profiles.wsl]
+                hotkey = "Ctrl+Shift+V"
+                steps = [{ type = "wsl" }]
+            "#,
+        );
+        let hotkey_id = settings.registered_hotkeys()[0].id();
+        let (sender, receiver) = unbounded_channel();
+        let mut runner = runner(settings, MockClipboard::with_text(r"C:\a.txt"));
+        runner.receiver = receiver;
+
+        sender.send(RuntimeEvent::ClipboardChanged).unwrap();
+        sender.send(RuntimeEvent::HotkeyPressed(hotkey_id)).unwrap();
+        drop(sender);
+        runner.run().await.unwrap();
+
+        assert_eq!(runner.clipboard.writes(), vec!["C:/a.txt", "/mnt/c/a.txt"]);
+    }
+
+    #[tokio::test]
+    async fn oversized_clipboard_text_is_not_converted() {
+        let source = r#"
+            auto_profile = "slash"
+            max_input_bytes = 16
+            max_lines = 2
+
+            [profiles.slash]
+            hotkey = "Ctrl+Shift+V"
+            steps = [{ type = "forward-slash" }]
+        "#;
+
+        for text in [r"C:\very\long\path.txt", "C:\\a\r\nC:\\b\r\nC:\\c"] {
+            let settings = settings(source);
+            let hotkey_id = settings.registered_hotkeys()[0].id();
+            let mut runner = runner(settings, MockClipboard::with_text(text));
+
+            runner.handle_clipboard_change().await;
+            runner.handle_hotkey(hotkey_id).await;
+
+            assert!(runner.clipboard.writes().is_empty(), "{text:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn conversions_are_recorded_in_audit_log() {
+        let settings = settings(
+            r#"
+                auto_profile = "slash"
+
+                [profiles.slash]
+                steps = [{ type = "forward-slash" }]
+
+                [profiles.wsl]
+                hotkey = "Ctrl+Shift+V"
+                steps = [{ type = "wsl" }]
+            "#,
+        );
+        let hotkey_id = settings.registered_hotkeys()[0].id();
+        let path = std::env::temp_dir().join(format!(
+            "pathclip-runner-audit-{}.jsonl",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let mut runner = runner(
+            settings,
+            MockClipboard::with_files(&[r"\\server\share\a.txt"]),
+        );
+        runner.audit = Some(AuditLog::new(path.clone(), false));
+
+        runner.handle_hotkey(hotkey_id).await;
+        runner.clipboard.set_text(r"C:\a.txt".to_string()).unwrap();
+        runner.handle_clipboard_change().await;
+
+        let log = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let entries = log
+            .lines()
+            .map(|line| serde_json::from_str::<AuditEntry>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].trigger, Trigger::Hotkey { id: hotkey_id });
+        assert_eq!(entries[0].profile, "wsl");
+        assert_eq!(entries[0].outcome, Outcome::Failed);
+        assert!(entries[0].error.is_some());
+        assert_eq!(entries[1].trigger, Trigger::Auto);
+        assert_eq!(entries[1].profile, "slash");
+        assert_eq!(entries[1].outcome, Outcome::Converted);
+        assert_eq!(entries[1].path_count, 1);
+        assert_eq!(entries[1].input, None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn failed_exec_stderr_is_only_logged_with_contents() {
+        let path = std::env::temp_dir().join(format!(
+            "pathclip-runner-audit-exec-{}.jsonl",
+            std::process::id()
+        ));
+        for log_contents in [false, true] {
+            let _ = std::fs::remove_file(&path);
+            let settings = settings(
+                r#"
+                    auto_profile = "echo"
+
+                    [profiles.echo]
+                    steps = [{ type = "exec", program = "sh", args = ["-c", "cat >&2; exit 1"] }]
+                "#,
+            );
+            let mut runner = runner(settings, MockClipboard::with_text(r"C:\secret.txt"));
+            runner.audit = Some(AuditLog::new(path.clone(), log_contents));
+
+            runner.handle_clipboard_change().await;
+
+            let log = std::fs::read_to_string(&path).unwrap();
+            let entry = serde_json::from_str::<AuditEntry>(log.trim()).unwrap();
+            assert_eq!(entry.outcome, Outcome::Failed);
+            assert_eq!(
+                entry.error.as_deref(),
+                Some("profile `echo` failed to run `sh`")
+            );
+            assert_eq!(log.contains("secret"), log_contents, "{log}");
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn unchanged_auto_conversions_are_recorded_in_audit_log() {
+        let settings = settings(
+            r#"
+                auto_profile = "noop"
+
+                [profiles.noop]
+                steps = [{ type = "regex", pattern = "^never$", replacement = "" }]
+            "#,
+        );
+        let path = std::env::temp_dir().join(format!(
+            "pathclip-runner-audit-unchanged-{}.jsonl",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let mut runner = runner(settings, MockClipboard::with_text(r"C:\a.txt"));
+        runner.audit = Some(AuditLog::new(path.clone(), false));
+
+        runner.handle_clipboard_change().await;
+
+        let log = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let entries = log
+            .lines()
+            .map(|line| serde_json::from_str::<AuditEntry>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].trigger, Trigger::Auto);
+        assert_eq!(entries[0].outcome, Outcome::Unchanged);
+        assert_eq!(entries[0].path_count, 1);
+        assert!(runner.clipboard.writes().is_empty());
+    }
+
+    #[tokio::test]
+    async fn status_lists_unregistered_hotkeys() {
+        let settings = settings(
+            r#"
+                toggle_hotkey = "Ctrl+Alt+P"
+
+                [profiles.wsl]
+                hotkey = "Ctrl+Shift+V"
+                steps = [{ type = "wsl" }]
+            "#,
+        );
+        let bindings = settings.hotkey_bindings();
+        assert_eq!(bindings[0].owner, "profile `wsl`");
+        assert_eq!(bindings[1].owner, "toggle_hotkey");
+        let failure = HotkeyFailure {
+            owner: bindings[0].owner.clone(),
+            hotkey: bindings[0].hotkey.to_string(),
+            error: "already registered".to_string(),
+        };
+        let mut runner =
+            runner(settings, MockClipboard::default()).with_hotkey_failures(vec![failure.clone()]);
+
+        let status = runner
+            .handle_control(ControlCommand::Status)
+            .await
+            .status
+            .unwrap();
+
+        assert_eq!(status.hotkey_failures, vec![failure]);
+        assert!(
+            status
+                .to_string()
+                .contains("unregistered hotkeys:\n  profile `wsl`")
+        );
+    }
+
+    #[tokio::test]
+    async fn control_commands_pause_resume_and_convert() {
+        let settings = settings(
+            r#"
+                auto_profile = "slash"
+
+                [profiles.slash]
+                steps = [{ type = "forward-slash" }]
+
+                [profiles.wsl]
+                hotkey = "Ctrl+Shift+V"
+                paste = true
+                steps = [{ type = "wsl" }]
+            "#,
+        );
+        let mut runner = runner(settings, MockClipboard::with_text(r"C:\a.txt"));
+
+        let paused = runner
+            .handle_control(ControlCommand::Pause(Some(Duration::from_secs(60))))
+            .await;
+        let status = runner
+            .handle_control(ControlCommand::Status)
+            .await
+            .status
+            .unwrap();
+        assert!(paused.ok);
+        assert_eq!(status.auto_conversion, AutoConversion::Paused);
+        assert!(status.resume_in_ms.is_some_and(|ms| ms <= 60_000));
+        assert_eq!(status.profiles, vec!["slash", "wsl"]);
+
+        runner.handle_control(ControlCommand::Resume).await;
+        assert_eq!(runner.auto_state(), AutoState::Active);
+
+        let unknown = runner
+            .handle_control(ControlCommand::Convert("missing".to_string()))
+            .await;
+        let converted = runner
+            .handle_control(ControlCommand::Convert("wsl".to_string()))
+            .await;
+        assert_eq!(unknown, Response::error("unknown profile `missing`"));
+        assert_eq!(
+            converted,
+            Response::ok("converted 1 paths with profile `wsl`")
+        );
+        assert_eq!(runner.clipboard.writes(), vec!["/mnt/c/a.txt"]);
+        assert_eq!(*runner.keyboard.pastes.lock().unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn reload_replaces_settings_and_reports_restart() {
+        let mut runner = runner(
+            settings(
+                r#"
+                    auto_profile = "slash"
+
+                    [profiles.slash]
+                    steps = [{ type = "forward-slash" }]
+                "#,
+            ),
+            MockClipboard::with_text(r"C:\a.txt"),
+        );
+
+        let same = runner
+            .handle_control(ControlCommand::Reload(Box::new(settings(
+                r#"
+                    auto_profile = "wsl"
+
+                    [profiles.wsl]
+                    steps = [{ type = "wsl" }]
+                "#,
+            ))))
+            .await;
+        assert_eq!(same, Response::ok("settings reloaded"));
+        runner.handle_clipboard_change().await;
+        assert_eq!(runner.clipboard.writes(), vec!["/mnt/c/a.txt"]);
+
+        let changed = runner
+            .handle_control(ControlCommand::Reload(Box::new(settings(
+                r#"
+                    auto_profile = "wsl"
+
+                    [profiles.wsl]
+                    hotkey = "Ctrl+Shift+V"
+                    steps = [{ type = "wsl" }]
+                "#,
+            ))))
+            .await;
+        assert!(changed.message.unwrap().contains("after restart"));
+    }
+
+    #[tokio::test]
+    async fn quit_command_stops_run_loop() {
+        let settings = settings(
+            r#"
+                [profiles.slash]
+                steps = [{ type = "forward-slash" }]
+            "#,
+        );
+        let (sender, receiver) = unbounded_channel();
+        let mut runner = runner(settings, MockClipboard::default());
+        runner.receiver = receiver;
+        let (reply, response) = tokio::sync::oneshot::channel();
+
+        sender
+            .send(RuntimeEvent::Control(ControlRequest {
+                command: ControlCommand::Quit,
+                reply,
+            }))
+            .unwrap();
+        runner.run().await.unwrap();
+
+        assert!(response.await.unwrap().ok);
+        assert!(!sender.is_closed());
     }
 
     #[test]
@@ -358,6 +1665,7 @@ mod tests {
         runner.recent_write = Some(RecentWrite {
             text: "C:/a.txt".to_string(),
             at: std::time::Instant::now(),
+            remaining: 1,
         });
 
         assert!(runner.is_recent_self_write("C:/a.txt"));
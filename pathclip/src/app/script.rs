@@ -0,0 +1,170 @@
+use std::fmt;
+use std::sync::Arc;
+
+use anyhow::{Result, anyhow, bail};
+use rhai::{AST, Array, Dynamic, Engine, Position, Scope};
+
+const MAX_OPERATIONS: u64 = 100_000;
+const MAX_STRING_SIZE: usize = 64 * 1024;
+const MAX_ARRAY_SIZE: usize = 10_000;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathParts {
+    pub drive: String,
+    pub server: String,
+    pub share: String,
+    pub segments: Vec<String>,
+}
+
+#[derive(Clone)]
+pub struct Script {
+    engine: Arc<Engine>,
+    ast: Arc<AST>,
+}
+
+impl fmt::Debug for Script {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Script").finish_non_exhaustive()
+    }
+}
+
+impl Script {
+    pub fn compile(source: &str) -> Result<Self> {
+        if source.trim().is_empty() {
+            bail!("script source is empty");
+        }
+
+        let mut engine = Engine::new();
+        engine
+            .set_strict_variables(true)
+            .set_max_operations(MAX_OPERATIONS)
+            .set_max_string_size(MAX_STRING_SIZE)
+            .set_max_array_size(MAX_ARRAY_SIZE);
+
+        let scope = scope("", PathParts::default());
+        let ast = engine
+            .compile_with_scope(&scope, source)
+            .map_err(|err| with_line(err.1, &err.0))?;
+        Ok(Self {
+            engine: Arc::new(engine),
+            ast: Arc::new(ast),
+        })
+    }
+
+    pub fn run(&self, path: &str, parts: PathParts) -> Result<String> {
+        let mut scope = scope(path, parts);
+        let value = self
+            .engine
+            .eval_ast_with_scope::<Dynamic>(&mut scope, &self.ast)
+            .map_err(|err| {
+                let position = err.position();
+                let mut err = *err;
+                with_line(position, err.clear_position())
+            })?;
+
+        let type_name = value.type_name();
+        value
+            .into_string()
+            .map_err(|_| anyhow!("script must return a string, got `{type_name}`"))
+    }
+}
+
+fn scope(path: &str, parts: PathParts) -> Scope<'static> {
+    let name = parts.segments.last().cloned().unwrap_or_default();
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem.to_string(), ext.to_string()),
+        _ => (name.clone(), String::new()),
+    };
+    let segments = parts
+        .segments
+        .into_iter()
+        .map(Dynamic::from)
+        .collect::<Array>();
+
+    let mut scope = Scope::new();
+    scope
+        .push("path", path.to_string())
+        .push("drive", parts.drive)
+        .push("server", parts.server)
+        .push("share", parts.share)
+        .push("segments", segments)
+        .push("name", name)
+        .push("stem", stem)
+        .push("ext", ext);
+    scope
+}
+
+fn with_line(position: Position, err: &dyn fmt::Display) -> anyhow::Error {
+    match position.line() {
+        Some(line) => anyhow!("line {line}: {err}"),
+        None => anyhow!("{err}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PathParts, Script};
+
+    fn parts(drive: &str, segments: &[&str]) -> PathParts {
+        PathParts {
+            drive: drive.to_string(),
+            segments: segments.iter().map(|segment| segment.to_string()).collect(),
+            ..PathParts::default()
+        }
+    }
+
+    #[test]
+    fn script_sees_path_and_components() {
+        let script = Script::compile(
+            r#"
+                if drive == "D" && segments[0] == "Work" {
+                    "//depot" + segments.extract(1).reduce(|sum, s| sum + "/" + s, "") + " (" + ext + ")"
+                } else {
+                    path
+                }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            script
+                .run(
+                    r"D:\Work\app\main.rs",
+                    parts("D", &["Work", "app", "main.rs"])
+                )
+                .unwrap(),
+            "//depot/app/main.rs (rs)"
+        );
+        assert_eq!(
+            script.run(r"C:\a.txt", parts("C", &["a.txt"])).unwrap(),
+            r"C:\a.txt"
+        );
+    }
+
+    #[test]
+    fn compile_errors_report_line() {
+        let error = Script::compile("let a = 1;\nlet b = ;").unwrap_err();
+        assert!(error.to_string().starts_with("line 2:"), "{error}");
+
+        let typo = Script::compile("pth").unwrap_err();
+        assert!(typo.to_string().contains("pth"), "{typo}");
+    }
+
+    #[test]
+    fn runtime_errors_report_line_and_non_string_results_fail() {
+        let script = Script::compile("let a = path;\nthrow \"unmapped\";").unwrap();
+        let error = script.run("C:\\a", PathParts::default()).unwrap_err();
+        assert!(error.to_string().starts_with("line 2:"), "{error}");
+        assert!(error.to_string().contains("unmapped"), "{error}");
+
+        let number = Script::compile("42").unwrap();
+        let error = number.run("C:\\a", PathParts::default()).unwrap_err();
+        assert!(
+            error.to_string().contains("must return a string"),
+            "{error}"
+        );
+
+        let endless = Script::compile("loop {}").unwrap();
+        assert!(endless.run("C:\\a", PathParts::default()).is_err());
+    }
+}
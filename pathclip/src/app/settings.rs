@@ -1,7 +1,8 @@
 use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use anyhow::{Context, Result, bail};
 use global_hotkey::hotkey::HotKey;
@@ -9,47 +10,160 @@ use regex::Regex;
 use serde::Deserialize;
 use tracing::info;
 
+use super::exec::ExecCommand;
+use super::foreground::ForegroundApp;
+use super::hotkey::HotkeyBinding;
+use super::script::Script;
+
 const DEFAULT_SETTINGS: &str = include_str!("../../pathclip.toml.example");
+const DEFAULT_RESTORE_DELAY: Duration = Duration::from_millis(500);
+const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(100);
+const DEFAULT_MAX_INPUT_BYTES: usize = 1024 * 1024;
+const DEFAULT_MAX_LINES: usize = 10_000;
+const DEFAULT_EXEC_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_EXEC_OUTPUT_BYTES: usize = 64 * 1024;
 
 #[derive(Debug)]
 pub struct Settings {
     auto_profile: Option<String>,
+    auto_rules: Vec<AutoRule>,
+    toggle_hotkey: Option<HotKey>,
+    suspend_for: Option<Duration>,
+    debounce: Duration,
+    max_input_bytes: Option<usize>,
+    max_lines: Option<usize>,
+    audit_log: Option<PathBuf>,
+    log_contents: bool,
     profiles: BTreeMap<String, Profile>,
     hotkey_profiles: HashMap<u32, String>,
 }
 
+#[derive(Debug, Clone)]
+struct AutoRule {
+    process: Option<String>,
+    window_title: Option<String>,
+    profile: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Profile {
     pub name: String,
     pub hotkey: Option<HotKey>,
+    pub keep_files: bool,
+    pub paste: bool,
+    pub restore_delay: Duration,
+    pub on_mixed: OnMixed,
+    pub join: Join,
     pub steps: Vec<TransformStep>,
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct Join {
+    pub style: JoinStyle,
+    pub header: String,
+    pub footer: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum JoinStyle {
+    #[default]
+    Lines,
+    Separator(String),
+    ShellArgs,
+    JsonArray,
+    Csv,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OnMixed {
+    #[default]
+    Abort,
+    SkipLine,
+}
+
 #[derive(Debug, Clone)]
 pub enum TransformStep {
-    Regex {
-        regex: Regex,
-        replacement: String,
-    },
+    Regex { regex: Regex, replacement: String },
     ForwardSlash,
     Wsl,
+    Windows,
     FileUri,
+    Exec(ExecCommand),
+    Script(Script),
 }
 
 #[derive(Debug, Deserialize)]
 struct SettingsFile {
     #[serde(default)]
     auto_profile: String,
+    #[serde(default)]
+    auto_rules: Vec<AutoRuleFile>,
+    #[serde(default)]
+    toggle_hotkey: String,
+    #[serde(default)]
+    suspend_for: String,
+    #[serde(default)]
+    debounce: String,
+    #[serde(default = "default_max_input_bytes")]
+    max_input_bytes: usize,
+    #[serde(default = "default_max_lines")]
+    max_lines: usize,
+    #[serde(default)]
+    audit_log: String,
+    #[serde(default)]
+    log_contents: bool,
     profiles: BTreeMap<String, ProfileFile>,
 }
 
+#[derive(Debug, Deserialize)]
+struct AutoRuleFile {
+    #[serde(default)]
+    process: String,
+    #[serde(default)]
+    window_title: String,
+    profile: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct ProfileFile {
     #[serde(default)]
     hotkey: String,
+    #[serde(default)]
+    keep_files: bool,
+    #[serde(default)]
+    paste: bool,
+    #[serde(default)]
+    restore_delay: String,
+    #[serde(default)]
+    on_mixed: OnMixed,
+    #[serde(default)]
+    join: JoinFile,
     steps: Vec<TransformStepFile>,
 }
 
+#[derive(Debug, Default, Deserialize)]
+struct JoinFile {
+    #[serde(default)]
+    style: JoinStyleFile,
+    separator: Option<String>,
+    #[serde(default)]
+    header: String,
+    #[serde(default)]
+    footer: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum JoinStyleFile {
+    #[default]
+    Lines,
+    Separator,
+    ShellArgs,
+    JsonArray,
+    Csv,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 enum TransformStepFile {
@@ -59,7 +173,19 @@ enum TransformStepFile {
     },
     ForwardSlash,
     Wsl,
+    Windows,
     FileUri,
+    Exec {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        timeout: String,
+        max_output_bytes: Option<usize>,
+    },
+    Script {
+        source: String,
+    },
 }
 
 impl Settings {
@@ -88,16 +214,109 @@ impl Settings {
             .and_then(|name| self.profiles.get(name))
     }
 
+    pub fn has_auto_conversion(&self) -> bool {
+        self.auto_profile.is_some() || self.auto_rules.iter().any(|rule| rule.profile.is_some())
+    }
+
+    pub fn has_auto_rules(&self) -> bool {
+        !self.auto_rules.is_empty()
+    }
+
+    pub fn auto_profile_for(&self, app: Option<&ForegroundApp>) -> Option<&Profile> {
+        let rule = app.and_then(|app| self.auto_rules.iter().find(|rule| rule.matches(app)));
+        match rule {
+            Some(rule) => rule
+                .profile
+                .as_ref()
+                .and_then(|name| self.profiles.get(name)),
+            None => self.auto_profile(),
+        }
+    }
+
+    /// 粘贴热键按下时的前台应用就是粘贴目标, 匹配到指定了 profile 的规则时改用规则的 profile
+    pub fn paste_profile_for<'a>(
+        &'a self,
+        hotkey_profile: &'a Profile,
+        app: Option<&ForegroundApp>,
+    ) -> &'a Profile {
+        app.and_then(|app| self.auto_rules.iter().find(|rule| rule.matches(app)))
+            .and_then(|rule| rule.profile.as_ref())
+            .and_then(|name| self.profiles.get(name))
+            .unwrap_or(hotkey_profile)
+    }
+
+    pub fn profile(&self, name: &str) -> Option<&Profile> {
+        self.profiles.get(name)
+    }
+
+    pub fn profile_names(&self) -> Vec<String> {
+        self.profiles.keys().cloned().collect()
+    }
+
     pub fn profile_for_hotkey(&self, hotkey_id: u32) -> Option<&Profile> {
         self.hotkey_profiles
             .get(&hotkey_id)
             .and_then(|name| self.profiles.get(name))
     }
 
+    pub fn is_toggle_hotkey(&self, hotkey_id: u32) -> bool {
+        self.toggle_hotkey
+            .is_some_and(|hotkey| hotkey.id() == hotkey_id)
+    }
+
+    pub fn suspend_for(&self) -> Option<Duration> {
+        self.suspend_for
+    }
+
+    pub fn debounce(&self) -> Duration {
+        self.debounce
+    }
+
+    pub fn check_input_size(&self, text: &str) -> Result<()> {
+        if let Some(max_bytes) = self.max_input_bytes
+            && text.len() > max_bytes
+        {
+            bail!(
+                "clipboard text has {} bytes, more than max_input_bytes = {max_bytes}",
+                text.len()
+            );
+        }
+        if let Some(max_lines) = self.max_lines
+            && text.lines().nth(max_lines).is_some()
+        {
+            bail!("clipboard text has more than max_lines = {max_lines} lines");
+        }
+        Ok(())
+    }
+
+    pub fn audit_log(&self) -> Option<&Path> {
+        self.audit_log.as_deref()
+    }
+
+    pub fn log_contents(&self) -> bool {
+        self.log_contents
+    }
+
     pub fn registered_hotkeys(&self) -> Vec<HotKey> {
+        self.hotkey_bindings()
+            .into_iter()
+            .map(|binding| binding.hotkey)
+            .collect()
+    }
+
+    pub fn hotkey_bindings(&self) -> Vec<HotkeyBinding> {
         self.profiles
             .values()
-            .filter_map(|profile| profile.hotkey)
+            .filter_map(|profile| {
+                profile.hotkey.map(|hotkey| HotkeyBinding {
+                    owner: format!("profile `{}`", profile.name),
+                    hotkey,
+                })
+            })
+            .chain(self.toggle_hotkey.map(|hotkey| HotkeyBinding {
+                owner: "toggle_hotkey".to_string(),
+                hotkey,
+            }))
             .collect()
     }
 
@@ -115,15 +334,19 @@ impl Settings {
                 bail!("profile `{name}` must contain at least one step");
             }
 
-            let hotkey = parse_hotkey(&name, &profile_file.hotkey)?;
+            let hotkey = parse_hotkey(&format!("profile `{name}`"), &profile_file.hotkey)?;
             if let Some(hotkey) = hotkey
                 && let Some(existing) = hotkey_profiles.insert(hotkey.id(), name.clone())
             {
-                    bail!(
-                        "profiles `{existing}` and `{name}` use the same hotkey `{hotkey}`"
-                    );
+                bail!("profiles `{existing}` and `{name}` use the same hotkey `{hotkey}`");
             }
 
+            let restore_delay = parse_duration(&profile_file.restore_delay)
+                .with_context(|| format!("profile `{name}` has an invalid restore_delay"))?
+                .unwrap_or(DEFAULT_RESTORE_DELAY);
+
+            let join = compile_join(&name, profile_file.join)?;
+
             let steps = profile_file
                 .steps
                 .into_iter()
@@ -135,6 +358,11 @@ impl Settings {
                 Profile {
                     name,
                     hotkey,
+                    keep_files: profile_file.keep_files,
+                    paste: profile_file.paste,
+                    restore_delay,
+                    on_mixed: profile_file.on_mixed,
+                    join,
                     steps,
                 },
             );
@@ -146,17 +374,56 @@ impl Settings {
             name => bail!("auto_profile references unknown profile `{name}`"),
         };
 
+        let auto_rules = file
+            .auto_rules
+            .into_iter()
+            .enumerate()
+            .map(|(index, rule)| compile_auto_rule(index, rule, &profiles))
+            .collect::<Result<Vec<_>>>()?;
+
+        let toggle_hotkey = parse_hotkey("toggle_hotkey", &file.toggle_hotkey)?;
+        if let Some(hotkey) = toggle_hotkey
+            && let Some(existing) = hotkey_profiles.get(&hotkey.id())
+        {
+            bail!("toggle_hotkey and profile `{existing}` use the same hotkey `{hotkey}`");
+        }
+        let suspend_for =
+            parse_duration(&file.suspend_for).context("suspend_for has an invalid duration")?;
+        let debounce = parse_duration(&file.debounce)
+            .context("debounce has an invalid duration")?
+            .unwrap_or(DEFAULT_DEBOUNCE);
+        let audit_log = resolve_audit_log_path(&file.audit_log)?;
+
         Ok(Self {
             auto_profile,
+            auto_rules,
+            toggle_hotkey,
+            suspend_for,
+            debounce,
+            max_input_bytes: (file.max_input_bytes > 0).then_some(file.max_input_bytes),
+            max_lines: (file.max_lines > 0).then_some(file.max_lines),
+            audit_log,
+            log_contents: file.log_contents,
             profiles,
             hotkey_profiles,
         })
     }
 }
 
+fn default_max_input_bytes() -> usize {
+    DEFAULT_MAX_INPUT_BYTES
+}
+
+fn default_max_lines() -> usize {
+    DEFAULT_MAX_LINES
+}
+
 fn resolve_settings_path(explicit_path: Option<PathBuf>) -> Result<Option<PathBuf>> {
-    let requested = explicit_path
-        .or_else(|| env::var_os("PATHCLIP_CONFIG").filter(|value| !value.is_empty()).map(PathBuf::from));
+    let requested = explicit_path.or_else(|| {
+        env::var_os("PATHCLIP_CONFIG")
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    });
 
     if let Some(path) = requested {
         if !path.is_file() {
@@ -171,7 +438,26 @@ fn resolve_settings_path(explicit_path: Option<PathBuf>) -> Result<Option<PathBu
     Ok(path.is_file().then_some(path))
 }
 
-fn parse_hotkey(profile_name: &str, source: &str) -> Result<Option<HotKey>> {
+fn resolve_audit_log_path(source: &str) -> Result<Option<PathBuf>> {
+    let source = source.trim();
+    if source.is_empty() {
+        return Ok(None);
+    }
+
+    match source
+        .strip_prefix("~/")
+        .or_else(|| source.strip_prefix("~\\"))
+    {
+        Some(rest) => {
+            let home =
+                dirs_next::home_dir().context("failed to determine the user home directory")?;
+            Ok(Some(home.join(rest)))
+        }
+        None => Ok(Some(PathBuf::from(source))),
+    }
+}
+
+fn parse_hotkey(owner: &str, source: &str) -> Result<Option<HotKey>> {
     let source = source.trim();
     if source.is_empty() {
         return Ok(None);
@@ -180,7 +466,136 @@ fn parse_hotkey(profile_name: &str, source: &str) -> Result<Option<HotKey>> {
     source
         .parse::<HotKey>()
         .map(Some)
-        .with_context(|| format!("profile `{profile_name}` has invalid hotkey `{source}`"))
+        .with_context(|| format!("{owner} has invalid hotkey `{source}`"))
+}
+
+pub(super) fn parse_duration(source: &str) -> Result<Option<Duration>> {
+    let source = source.trim();
+    if source.is_empty() {
+        return Ok(None);
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = source;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let unit_len = rest[digits..]
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len() - digits);
+        let value = rest[..digits]
+            .parse::<u64>()
+            .with_context(|| format!("`{source}` is not a duration like `30s`, `5m` or `1h30m`"))?;
+        let part = match &rest[digits..digits + unit_len] {
+            "ms" => Some(Duration::from_millis(value)),
+            "s" => Some(Duration::from_secs(value)),
+            "m" => value.checked_mul(60).map(Duration::from_secs),
+            "h" => value.checked_mul(60 * 60).map(Duration::from_secs),
+            unit => bail!("`{source}` uses unknown duration unit `{unit}`"),
+        };
+        total = part
+            .and_then(|part| total.checked_add(part))
+            .with_context(|| format!("`{source}` is too large for a duration"))?;
+        rest = &rest[digits + unit_len..];
+    }
+
+    if total.is_zero() {
+        bail!("`{source}` must be longer than zero");
+    }
+    Ok(Some(total))
+}
+
+fn compile_auto_rule(
+    index: usize,
+    rule: AutoRuleFile,
+    profiles: &BTreeMap<String, Profile>,
+) -> Result<AutoRule> {
+    let pattern = |source: &str| {
+        let source = source.trim();
+        (!source.is_empty()).then(|| source.to_lowercase())
+    };
+    let process = pattern(&rule.process);
+    let window_title = pattern(&rule.window_title);
+    if process.is_none() && window_title.is_none() {
+        bail!("auto_rules[{index}] must set `process` or `window_title`");
+    }
+
+    let profile = match rule.profile.trim() {
+        "" => None,
+        name if profiles.contains_key(name) => Some(name.to_string()),
+        name => bail!("auto_rules[{index}] references unknown profile `{name}`"),
+    };
+
+    Ok(AutoRule {
+        process,
+        window_title,
+        profile,
+    })
+}
+
+impl AutoRule {
+    fn matches(&self, app: &ForegroundApp) -> bool {
+        self.process
+            .as_deref()
+            .is_none_or(|pattern| wildcard_match(pattern, &app.process.to_lowercase()))
+            && self
+                .window_title
+                .as_deref()
+                .is_none_or(|pattern| wildcard_match(pattern, &app.window_title.to_lowercase()))
+    }
+}
+
+fn wildcard_match(pattern: &str, input: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let input = input.chars().collect::<Vec<_>>();
+    let (mut p, mut i) = (0, 0);
+    let mut backtrack = None;
+
+    while i < input.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, i));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == input[i] => {
+                p += 1;
+                i += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    backtrack = Some((star, matched + 1));
+                    p = star + 1;
+                    i = matched + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+fn compile_join(profile_name: &str, join: JoinFile) -> Result<Join> {
+    let style = match (join.style, join.separator) {
+        (JoinStyleFile::Separator, Some(separator)) => JoinStyle::Separator(separator),
+        (JoinStyleFile::Separator, None) => {
+            bail!("profile `{profile_name}` uses join style `separator` without `separator`")
+        }
+        (_, Some(_)) => {
+            bail!("profile `{profile_name}` sets `separator` but join style is not `separator`")
+        }
+        (JoinStyleFile::Lines, None) => JoinStyle::Lines,
+        (JoinStyleFile::ShellArgs, None) => JoinStyle::ShellArgs,
+        (JoinStyleFile::JsonArray, None) => JoinStyle::JsonArray,
+        (JoinStyleFile::Csv, None) => JoinStyle::Csv,
+    };
+
+    Ok(Join {
+        style,
+        header: join.header,
+        footer: join.footer,
+    })
 }
 
 fn compile_step(profile_name: &str, step: TransformStepFile) -> Result<TransformStep> {
@@ -196,13 +611,54 @@ fn compile_step(profile_name: &str, step: TransformStepFile) -> Result<Transform
         }
         TransformStepFile::ForwardSlash => Ok(TransformStep::ForwardSlash),
         TransformStepFile::Wsl => Ok(TransformStep::Wsl),
+        TransformStepFile::Windows => Ok(TransformStep::Windows),
         TransformStepFile::FileUri => Ok(TransformStep::FileUri),
+        TransformStepFile::Exec {
+            program,
+            args,
+            timeout,
+            max_output_bytes,
+        } => {
+            if program.trim().is_empty() {
+                bail!("profile `{profile_name}` has an exec step without `program`");
+            }
+            let timeout = parse_duration(&timeout)
+                .with_context(|| format!("profile `{profile_name}` has an invalid exec timeout"))?
+                .unwrap_or(DEFAULT_EXEC_TIMEOUT);
+            let max_output_bytes = match max_output_bytes {
+                Some(0) => {
+                    bail!("profile `{profile_name}` has an exec step with max_output_bytes = 0")
+                }
+                Some(bytes) => bytes,
+                None => DEFAULT_EXEC_OUTPUT_BYTES,
+            };
+            Ok(TransformStep::Exec(ExecCommand {
+                program,
+                args,
+                timeout,
+                max_output_bytes,
+            }))
+        }
+        TransformStepFile::Script { source } => {
+            let script = Script::compile(&source)
+                .with_context(|| format!("profile `{profile_name}` has invalid script"))?;
+            Ok(TransformStep::Script(script))
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Settings;
+    use std::time::Duration;
+
+    use super::{ForegroundApp, Settings, TransformStep, parse_duration};
+
+    fn app(process: &str, window_title: &str) -> ForegroundApp {
+        ForegroundApp {
+            process: process.to_string(),
+            window_title: window_title.to_string(),
+        }
+    }
 
     #[test]
     fn default_settings_are_valid() {
@@ -277,4 +733,316 @@ mod tests {
 
         assert!(error.to_string().contains("same hotkey"));
     }
+
+    #[test]
+    fn auto_rules_pick_profile_by_foreground_app() {
+        let settings = Settings::parse(
+            r#"
+                auto_profile = "slash"
+
+                [[auto_rules]]
+                process = "WindowsTerminal.exe"
+                profile = "wsl"
+
+                [[auto_rules]]
+                window_title = "*Visual Studio Code*"
+                profile = "slash"
+
+                [[auto_rules]]
+                process = "regedit.exe"
+                profile = ""
+
+                [profiles.slash]
+                steps = [{ type = "forward-slash" }]
+
+                [profiles.wsl]
+                steps = [{ type = "wsl" }]
+            "#,
+        )
+        .unwrap();
+
+        let profile_name = |app: Option<&ForegroundApp>| {
+            settings
+                .auto_profile_for(app)
+                .map(|profile| profile.name.as_str())
+        };
+        assert_eq!(
+            profile_name(Some(&app("windowsterminal.exe", "pwsh"))),
+            Some("wsl")
+        );
+        assert_eq!(
+            profile_name(Some(&app("Code.exe", "main.rs - Visual Studio Code"))),
+            Some("slash")
+        );
+        assert_eq!(
+            profile_name(Some(&app("regedit.exe", "Registry Editor"))),
+            None
+        );
+        assert_eq!(
+            profile_name(Some(&app("notepad.exe", "a.txt"))),
+            Some("slash")
+        );
+        assert_eq!(profile_name(None), Some("slash"));
+    }
+
+    #[test]
+    fn paste_profile_follows_rules_for_the_paste_target() {
+        let settings = Settings::parse(
+            r#"
+                [[auto_rules]]
+                process = "WindowsTerminal.exe"
+                profile = "wsl"
+
+                [[auto_rules]]
+                process = "regedit.exe"
+                profile = ""
+
+                [profiles.paste]
+                hotkey = "Ctrl+Shift+V"
+                paste = true
+                steps = [{ type = "forward-slash" }]
+
+                [profiles.wsl]
+                steps = [{ type = "wsl" }]
+            "#,
+        )
+        .unwrap();
+
+        let hotkey_profile = settings.profile("paste").unwrap();
+        let profile_name = |app: Option<&ForegroundApp>| {
+            settings
+                .paste_profile_for(hotkey_profile, app)
+                .name
+                .as_str()
+        };
+        assert_eq!(
+            profile_name(Some(&app("WindowsTerminal.exe", "bash"))),
+            "wsl"
+        );
+        assert_eq!(profile_name(Some(&app("regedit.exe", ""))), "paste");
+        assert_eq!(profile_name(Some(&app("notepad.exe", ""))), "paste");
+        assert_eq!(profile_name(None), "paste");
+    }
+
+    #[test]
+    fn auto_rules_enable_automatic_conversion_without_default_profile() {
+        let settings = Settings::parse(
+            r#"
+                auto_profile = ""
+
+                [[auto_rules]]
+                process = "WindowsTerminal.exe"
+                profile = "wsl"
+
+                [profiles.wsl]
+                steps = [{ type = "wsl" }]
+            "#,
+        )
+        .unwrap();
+
+        assert!(settings.auto_profile().is_none());
+        assert!(settings.has_auto_conversion());
+        assert!(
+            settings
+                .auto_profile_for(Some(&app("explorer.exe", "")))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn auto_rule_without_matcher_is_rejected() {
+        let error = Settings::parse(
+            r#"
+                [[auto_rules]]
+                profile = "slash"
+
+                [profiles.slash]
+                steps = [{ type = "forward-slash" }]
+            "#,
+        )
+        .unwrap_err();
+
+        assert!(error.to_string().contains("`process` or `window_title`"));
+    }
+
+    #[test]
+    fn auto_rule_with_unknown_profile_is_rejected() {
+        let error = Settings::parse(
+            r#"
+                [[auto_rules]]
+                process = "Code.exe"
+                profile = "missing"
+
+                [profiles.slash]
+                steps = [{ type = "forward-slash" }]
+            "#,
+        )
+        .unwrap_err();
+
+        assert!(error.to_string().contains("unknown profile"));
+    }
+
+    #[test]
+    fn toggle_hotkey_is_registered_with_profile_hotkeys() {
+        let settings = Settings::parse(
+            r#"
+                toggle_hotkey = "Ctrl+Alt+P"
+                suspend_for = "5m"
+
+                [profiles.slash]
+                hotkey = "Ctrl+Shift+V"
+                steps = [{ type = "forward-slash" }]
+            "#,
+        )
+        .unwrap();
+
+        let hotkeys = settings.registered_hotkeys();
+        assert_eq!(hotkeys.len(), 2);
+        assert!(settings.is_toggle_hotkey(hotkeys[1].id()));
+        assert!(!settings.is_toggle_hotkey(hotkeys[0].id()));
+        assert_eq!(settings.suspend_for(), Some(Duration::from_secs(300)));
+    }
+
+    #[test]
+    fn toggle_hotkey_conflicting_with_profile_is_rejected() {
+        let error = Settings::parse(
+            r#"
+                toggle_hotkey = "Ctrl+Shift+V"
+
+                [profiles.slash]
+                hotkey = "Ctrl+Shift+V"
+                steps = [{ type = "forward-slash" }]
+            "#,
+        )
+        .unwrap_err();
+
+        assert!(error.to_string().contains("same hotkey"));
+    }
+
+    #[test]
+    fn durations_accept_combined_units() {
+        assert_eq!(parse_duration("").unwrap(), None);
+        assert_eq!(
+            parse_duration("30s").unwrap(),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_duration("1h30m").unwrap(),
+            Some(Duration::from_secs(5400))
+        );
+        assert_eq!(
+            parse_duration("250ms").unwrap(),
+            Some(Duration::from_millis(250))
+        );
+        assert!(parse_duration("5").is_err());
+        assert!(parse_duration("5d").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("0s").is_err());
+        assert!(parse_duration("99999999999999999h").is_err());
+        assert!(parse_duration("18446744073709551615s1s").is_err());
+    }
+
+    #[test]
+    fn input_limits_use_defaults_and_zero_disables_them() {
+        let source = r#"
+            [profiles.slash]
+            steps = [{ type = "forward-slash" }]
+        "#;
+        let defaults = Settings::parse(source).unwrap();
+        assert_eq!(defaults.debounce(), Duration::from_millis(100));
+        assert!(defaults.check_input_size(&"a".repeat(1024 * 1024)).is_ok());
+        assert!(
+            defaults
+                .check_input_size(&"a".repeat(1024 * 1024 + 1))
+                .is_err()
+        );
+        assert!(defaults.check_input_size(&"a\n".repeat(10_000)).is_ok());
+        assert!(defaults.check_input_size(&"a\n".repeat(10_001)).is_err());
+
+        let unlimited = Settings::parse(&format!(
+            "debounce = \"20ms\"\nmax_input_bytes = 0\nmax_lines = 0\n{source}"
+        ))
+        .unwrap();
+        assert_eq!(unlimited.debounce(), Duration::from_millis(20));
+        assert!(
+            unlimited
+                .check_input_size(&"a\n".repeat(1024 * 1024))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn exec_step_uses_defaults_and_requires_program() {
+        let settings = Settings::parse(
+            r#"
+                [profiles.depot]
+                steps = [{ type = "exec", program = "p4path", args = ["--depot"] }]
+            "#,
+        )
+        .unwrap();
+        let missing = Settings::parse(
+            r#"
+                [profiles.depot]
+                steps = [{ type = "exec", program = "" }]
+            "#,
+        )
+        .unwrap_err();
+
+        let TransformStep::Exec(command) = &settings.profiles["depot"].steps[0] else {
+            panic!("expected exec step");
+        };
+        assert_eq!(command.args, vec!["--depot"]);
+        assert_eq!(command.timeout, Duration::from_secs(5));
+        assert_eq!(command.max_output_bytes, 64 * 1024);
+        assert!(missing.to_string().contains("without `program`"));
+    }
+
+    #[test]
+    fn script_errors_include_profile_and_line() {
+        let error = Settings::parse(
+            r#"
+                [profiles.depot]
+                steps = [{ type = "script", source = """
+                    if drive == "D" {
+                        "//depot" +
+                    }
+                """ }]
+            "#,
+        )
+        .unwrap_err();
+
+        let message = format!("{error:#}");
+        assert!(
+            message.contains("profile `depot` has invalid script"),
+            "{message}"
+        );
+        assert!(message.contains("line 3"), "{message}");
+    }
+
+    #[test]
+    fn join_separator_requires_separator_style() {
+        let missing = Settings::parse(
+            r#"
+                [profiles.args]
+                join = { style = "separator" }
+                steps = [{ type = "forward-slash" }]
+            "#,
+        )
+        .unwrap_err();
+        let misplaced = Settings::parse(
+            r#"
+                [profiles.args]
+                join = { style = "csv", separator = ";" }
+                steps = [{ type = "forward-slash" }]
+            "#,
+        )
+        .unwrap_err();
+
+        assert!(missing.to_string().contains("without `separator`"));
+        assert!(
+            misplaced
+                .to_string()
+                .contains("join style is not `separator`")
+        );
+    }
 }
@@ -1,43 +1,78 @@
-use std::path::Path;
-
 use anyhow::{Context, Result, bail};
 use url::Url;
 
-use super::settings::{Profile, TransformStep};
+use super::script::PathParts;
+use super::settings::{Join, JoinStyle, OnMixed, Profile, TransformStep};
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct TransformResult {
     pub output: String,
     pub path_count: usize,
+    pub skipped: usize,
 }
 
 pub fn transform_text(profile: &Profile, input: &str) -> Result<Option<TransformResult>> {
     let lines = split_lines(input);
-    let path_count = lines
-        .iter()
-        .filter(|line| !line.content.is_empty())
-        .count();
-
-    if path_count == 0
-        || lines
-            .iter()
-            .filter(|line| !line.content.is_empty())
-            .any(|line| !is_windows_absolute_path(line.content))
-    {
+    let mut non_empty = lines.iter().filter(|line| !line.content.is_empty());
+    let has_paths = match profile.on_mixed {
+        OnMixed::Abort => {
+            let mut non_empty = non_empty.peekable();
+            non_empty.peek().is_some()
+                && non_empty.all(|line| is_source_path(profile, line.content))
+        }
+        OnMixed::SkipLine => non_empty.any(|line| is_source_path(profile, line.content)),
+    };
+    if !has_paths {
         return Ok(None);
     }
 
     let mut output = String::with_capacity(input.len());
+    // 非 lines 的拼接方式只拼接转换成功的路径, 跳过的行不能混进命令行参数或 JSON 数组
+    let mut items = Vec::new();
+    let mut path_count = 0;
+    let mut skipped = 0;
+    let mut first_error = None;
     for line in lines {
         if line.content.is_empty() {
-            output.push_str(line.content);
-        } else {
-            output.push_str(&apply_profile(profile, line.content)?);
+            output.push_str(line.ending);
+            continue;
         }
+
+        let item = if !is_source_path(profile, line.content) {
+            skipped += 1;
+            line.content.to_string()
+        } else {
+            match apply_profile(profile, line.content) {
+                Ok(converted) => {
+                    path_count += 1;
+                    items.push(converted.clone());
+                    converted
+                }
+                Err(err) if profile.on_mixed == OnMixed::SkipLine => {
+                    skipped += 1;
+                    first_error.get_or_insert(err);
+                    line.content.to_string()
+                }
+                Err(err) => return Err(err),
+            }
+        };
+        output.push_str(&item);
         output.push_str(line.ending);
     }
 
-    Ok(Some(TransformResult { output, path_count }))
+    if path_count == 0 {
+        return Err(first_error.expect("skipped path lines must have an error"));
+    }
+
+    let output = match profile.join.style {
+        JoinStyle::Lines => output,
+        _ => join_items(&profile.join.style, &items),
+    };
+    Ok(Some(TransformResult {
+        output: wrap_output(&profile.join, output, path_count),
+        path_count,
+        skipped,
+    }))
 }
 
 pub fn transform_files(profile: &Profile, paths: &[String]) -> Result<TransformResult> {
@@ -45,18 +80,116 @@ pub fn transform_files(profile: &Profile, paths: &[String]) -> Result<TransformR
         bail!("clipboard file list is empty");
     }
 
-    let output = paths
-        .iter()
-        .map(|path| apply_profile(profile, path))
-        .collect::<Result<Vec<_>>>()?
-        .join("\r\n");
+    let mut converted = Vec::with_capacity(paths.len());
+    let mut skipped = 0;
+    let mut first_error = None;
+    for path in paths {
+        match apply_profile(profile, path) {
+            Ok(output) => converted.push(output),
+            Err(err) if profile.on_mixed == OnMixed::SkipLine => {
+                skipped += 1;
+                first_error.get_or_insert(err);
+                // 只有 lines 保留转换失败的原路径, 其他拼接方式只输出转换成功的路径
+                if profile.join.style == JoinStyle::Lines {
+                    converted.push(path.clone());
+                }
+            }
+            Err(err) => return Err(err),
+        }
+    }
 
+    if skipped == paths.len() {
+        return Err(first_error.expect("skipped files must have an error"));
+    }
+
+    let path_count = paths.len() - skipped;
     Ok(TransformResult {
-        output,
-        path_count: paths.len(),
+        output: wrap_output(
+            &profile.join,
+            join_items(&profile.join.style, &converted),
+            path_count,
+        ),
+        path_count,
+        skipped,
     })
 }
 
+fn join_items(style: &JoinStyle, items: &[String]) -> String {
+    match style {
+        JoinStyle::Lines => items.join("\r\n"),
+        JoinStyle::Separator(separator) => items.join(separator),
+        JoinStyle::ShellArgs => items
+            .iter()
+            .map(|item| shell_quote(item))
+            .collect::<Vec<_>>()
+            .join(" "),
+        JoinStyle::JsonArray => format!(
+            "[{}]",
+            items
+                .iter()
+                .map(|item| json_quote(item))
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+        JoinStyle::Csv => items
+            .iter()
+            .map(|item| csv_quote(item))
+            .collect::<Vec<_>>()
+            .join(","),
+    }
+}
+
+fn wrap_output(join: &Join, output: String, count: usize) -> String {
+    if join.header.is_empty() && join.footer.is_empty() {
+        return output;
+    }
+
+    let count = count.to_string();
+    format!(
+        "{}{output}{}",
+        join.header.replace("{count}", &count),
+        join.footer.replace("{count}", &count)
+    )
+}
+
+fn shell_quote(input: &str) -> String {
+    let is_safe = !input.is_empty()
+        && input.chars().all(|c| {
+            c.is_ascii_alphanumeric()
+                || matches!(c, '_' | '-' | '.' | '/' | ':' | '@' | '%' | '+' | '=' | ',')
+        });
+    if is_safe {
+        return input.to_string();
+    }
+    format!("'{}'", input.replace('\'', r"'\''"))
+}
+
+fn json_quote(input: &str) -> String {
+    let mut output = String::with_capacity(input.len() + 2);
+    output.push('"');
+    for c in input.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if u32::from(c) < 0x20 => output.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+    output
+}
+
+fn csv_quote(input: &str) -> String {
+    if input.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", input.replace('"', "\"\""))
+    } else {
+        input.to_string()
+    }
+}
+
 fn apply_profile(profile: &Profile, input: &str) -> Result<String> {
     let mut current = input.to_string();
     for step in &profile.steps {
@@ -65,11 +198,27 @@ fn apply_profile(profile: &Profile, input: &str) -> Result<String> {
                 regex.replace_all(&current, replacement).into_owned()
             }
             TransformStep::ForwardSlash => to_forward_slash(&current),
-            TransformStep::Wsl => to_wsl(&current)
-                .with_context(|| format!("profile `{}` failed to convert a WSL path", profile.name))?,
+            TransformStep::Wsl => to_wsl(&current).with_context(|| {
+                format!("profile `{}` failed to convert a WSL path", profile.name)
+            })?,
+            TransformStep::Windows => to_windows(&current).with_context(|| {
+                format!(
+                    "profile `{}` failed to convert a WSL mount path",
+                    profile.name
+                )
+            })?,
             TransformStep::FileUri => to_file_uri(&current).with_context(|| {
                 format!("profile `{}` failed to convert a file URI", profile.name)
             })?,
+            TransformStep::Exec(command) => command.run(&current).with_context(|| {
+                format!(
+                    "profile `{}` failed to run `{}`",
+                    profile.name, command.program
+                )
+            })?,
+            TransformStep::Script(script) => script
+                .run(&current, path_parts(&current))
+                .with_context(|| format!("profile `{}` script failed", profile.name))?,
         };
     }
     Ok(current)
@@ -109,11 +258,62 @@ fn to_wsl(input: &str) -> Result<String> {
     bail!("input is not an absolute Windows drive path");
 }
 
+fn to_windows(input: &str) -> Result<String> {
+    let Some((drive, remainder)) = split_mount_path(input) else {
+        bail!("input is not a path under a WSL drive mount");
+    };
+    Ok(format!(
+        "{}:\\{}",
+        drive.to_ascii_uppercase(),
+        remainder.trim_start_matches('/').replace('/', "\\")
+    ))
+}
+
 fn to_file_uri(input: &str) -> Result<String> {
     let normalized = normalize_extended_path(input);
-    Url::from_file_path(Path::new(&normalized))
-        .map(|url| url.to_string())
-        .map_err(|_| anyhow::anyhow!("input is not a valid absolute file path"))
+    let mut url = Url::parse("file:///").expect("file URI base must be valid");
+
+    let segments = if let Some((drive, remainder)) = split_drive_path(&normalized) {
+        std::iter::once(format!("{drive}:"))
+            .chain(remainder[1..].split(['\\', '/']).map(str::to_string))
+            .collect::<Vec<_>>()
+    } else if is_unc_path(&normalized) {
+        let mut components = normalized[2..].split(['\\', '/']);
+        let server = components.next().unwrap_or_default();
+        url.set_host(Some(server))
+            .map_err(|_| anyhow::anyhow!("UNC server name is not a valid host"))?;
+        components.map(str::to_string).collect()
+    } else {
+        bail!("input is not a valid absolute file path");
+    };
+
+    url.path_segments_mut()
+        .map_err(|_| anyhow::anyhow!("file URI cannot have path segments"))?
+        .clear()
+        .extend(segments);
+    Ok(url.to_string())
+}
+
+fn path_parts(input: &str) -> PathParts {
+    let normalized = normalize_extended_path(input);
+    let mut parts = PathParts::default();
+    let rest = if let Some((drive, rest)) = split_drive_path(&normalized) {
+        parts.drive = drive.to_ascii_uppercase().to_string();
+        rest
+    } else if is_unc_path(&normalized) {
+        let mut components = normalized[2..].splitn(3, ['\\', '/']);
+        parts.server = components.next().unwrap_or_default().to_string();
+        parts.share = components.next().unwrap_or_default().to_string();
+        components.next().unwrap_or_default()
+    } else {
+        &normalized
+    };
+    parts.segments = rest
+        .split(['\\', '/'])
+        .filter(|segment| !segment.is_empty())
+        .map(str::to_string)
+        .collect();
+    parts
 }
 
 fn normalize_extended_path(input: &str) -> String {
@@ -155,6 +355,32 @@ fn split_drive_path(input: &str) -> Option<(char, &str)> {
     Some((bytes[0] as char, &input[2..]))
 }
 
+/// 包含 windows 步骤的 profile 转换 WSL 挂载路径, 其他 profile 转换 Windows 绝对路径
+fn is_source_path(profile: &Profile, input: &str) -> bool {
+    if profile
+        .steps
+        .iter()
+        .any(|step| matches!(step, TransformStep::Windows))
+    {
+        !input.contains('\0') && split_mount_path(strip_outer_quotes(input)).is_some()
+    } else {
+        is_windows_absolute_path(input)
+    }
+}
+
+/// `/mnt/d/Work` 拆分为 `('d', "/Work")`
+fn split_mount_path(input: &str) -> Option<(char, &str)> {
+    let rest = input.strip_prefix("/mnt/")?;
+    let bytes = rest.as_bytes();
+    if bytes.is_empty()
+        || !bytes[0].is_ascii_alphabetic()
+        || bytes.get(1).is_some_and(|byte| *byte != b'/')
+    {
+        return None;
+    }
+    Some((bytes[0] as char, &rest[1..]))
+}
+
 fn is_windows_absolute_path(input: &str) -> bool {
     if input.contains('\0') {
         return false;
@@ -228,14 +454,24 @@ fn split_lines(input: &str) -> Vec<Line<'_>> {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use regex::Regex;
 
-    use super::{Profile, TransformStep, transform_files, transform_text};
+    use super::{
+        Join, JoinStyle, OnMixed, PathParts, Profile, TransformStep, path_parts, transform_files,
+        transform_text,
+    };
 
     fn profile(name: &str, steps: Vec<TransformStep>) -> Profile {
         Profile {
             name: name.to_string(),
             hotkey: None,
+            keep_files: false,
+            paste: false,
+            restore_delay: Duration::ZERO,
+            on_mixed: OnMixed::Abort,
+            join: Join::default(),
             steps,
         }
     }
@@ -264,7 +500,11 @@ mod tests {
     #[test]
     fn ignores_non_ascii_plain_text_without_panicking() {
         let profile = profile("slash", vec![TransformStep::ForwardSlash]);
-        assert!(transform_text(&profile, "这不是路径文本").unwrap().is_none());
+        assert!(
+            transform_text(&profile, "这不是路径文本")
+                .unwrap()
+                .is_none()
+        );
     }
 
     #[test]
@@ -292,12 +532,62 @@ mod tests {
         assert_eq!(result.output, "C:/a/b");
     }
 
+    #[cfg(unix)]
     #[test]
-    fn converts_drive_and_wsl_unc_paths_to_wsl() {
-        let profile = profile("wsl", vec![TransformStep::Wsl]);
-        let drive = transform_text(&profile, "D:\\Work\\a")
+    fn exec_step_converts_each_path_and_reports_profile() {
+        use crate::app::exec::ExecCommand;
+
+        let exec = |script: &str| {
+            TransformStep::Exec(ExecCommand {
+                program: "sh".to_string(),
+                args: vec!["-c".to_string(), script.to_string()],
+                timeout: Duration::from_secs(5),
+                max_output_bytes: 1024,
+            })
+        };
+        let depot = profile(
+            "depot",
+            vec![
+                TransformStep::ForwardSlash,
+                exec("sed 's|^C:/src|//depot/main|'"),
+            ],
+        );
+        let failing = profile("broken", vec![exec("exit 1")]);
+
+        let result = transform_text(&depot, "C:\\src\\a.rs\r\nC:\\src\\b.rs")
             .unwrap()
             .unwrap();
+        let error = transform_text(&failing, r"C:\a.txt").unwrap_err();
+
+        assert_eq!(result.output, "//depot/main/a.rs\r\n//depot/main/b.rs");
+        assert!(format!("{error:#}").contains("profile `broken` failed to run `sh`"));
+    }
+
+    #[test]
+    fn path_parts_split_drive_and_unc_paths() {
+        assert_eq!(
+            path_parts(r"\\?\d:\Work\a.txt"),
+            PathParts {
+                drive: "D".to_string(),
+                segments: vec!["Work".to_string(), "a.txt".to_string()],
+                ..PathParts::default()
+            }
+        );
+        assert_eq!(
+            path_parts(r"\\server\share\dir\"),
+            PathParts {
+                server: "server".to_string(),
+                share: "share".to_string(),
+                segments: vec!["dir".to_string()],
+                ..PathParts::default()
+            }
+        );
+    }
+
+    #[test]
+    fn converts_drive_and_wsl_unc_paths_to_wsl() {
+        let profile = profile("wsl", vec![TransformStep::Wsl]);
+        let drive = transform_text(&profile, "D:\\Work\\a").unwrap().unwrap();
         let unc = transform_text(&profile, r"\\wsl.localhost\Ubuntu\home\me")
             .unwrap()
             .unwrap();
@@ -345,4 +635,144 @@ mod tests {
         let paths = vec!["C:\\a".to_string(), r"\\server\share\b".to_string()];
         assert!(transform_files(&profile, &paths).is_err());
     }
+
+    #[test]
+    fn skip_line_converts_path_lines_and_keeps_the_rest() {
+        let mut profile = profile("wsl", vec![TransformStep::Wsl]);
+        profile.on_mixed = OnMixed::SkipLine;
+        let result = transform_text(
+            &profile,
+            "C:\\a\r\nnot a path\r\n\\\\server\\share\\b\r\nD:\\c",
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(
+            result.output,
+            "/mnt/c/a\r\nnot a path\r\n\\\\server\\share\\b\r\n/mnt/d/c"
+        );
+        assert_eq!(result.path_count, 2);
+        assert_eq!(result.skipped, 2);
+    }
+
+    #[test]
+    fn skip_line_without_any_path_is_left_alone() {
+        let mut profile = profile("slash", vec![TransformStep::ForwardSlash]);
+        profile.on_mixed = OnMixed::SkipLine;
+        assert!(transform_text(&profile, "plain\ntext").unwrap().is_none());
+    }
+
+    #[test]
+    fn skip_line_reports_error_when_every_path_fails() {
+        let mut profile = profile("wsl", vec![TransformStep::Wsl]);
+        profile.on_mixed = OnMixed::SkipLine;
+        assert!(transform_text(&profile, "\\\\server\\share\\a\nplain").is_err());
+    }
+
+    #[test]
+    fn skip_line_keeps_failed_files_unchanged() {
+        let mut profile = profile("wsl", vec![TransformStep::Wsl]);
+        profile.on_mixed = OnMixed::SkipLine;
+        let paths = vec!["C:\\a".to_string(), r"\\server\share\b".to_string()];
+        let result = transform_files(&profile, &paths).unwrap();
+
+        assert_eq!(result.output, "/mnt/c/a\r\n\\\\server\\share\\b");
+        assert_eq!(result.path_count, 1);
+        assert_eq!(result.skipped, 1);
+    }
+
+    #[test]
+    fn joins_files_as_quoted_shell_args_with_header() {
+        let mut profile = profile("args", vec![TransformStep::Wsl]);
+        profile.join = Join {
+            style: JoinStyle::ShellArgs,
+            header: "git add -- ".to_string(),
+            footer: String::new(),
+        };
+        let paths = vec![
+            r"C:\src\main.rs".to_string(),
+            r"C:\My Docs\it's.txt".to_string(),
+        ];
+        let result = transform_files(&profile, &paths).unwrap();
+
+        assert_eq!(
+            result.output,
+            r"git add -- /mnt/c/src/main.rs '/mnt/c/My Docs/it'\''s.txt'"
+        );
+    }
+
+    #[test]
+    fn joins_multi_line_text_as_json_array() {
+        let mut profile = profile("json", vec![TransformStep::ForwardSlash]);
+        profile.join.style = JoinStyle::JsonArray;
+        let result = transform_text(&profile, "C:\\a \"b\"\r\n\r\nD:\\c\r\n")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(result.output, r#"["C:/a \"b\"","D:/c"]"#);
+    }
+
+    #[test]
+    fn joins_files_as_csv_with_count_template() {
+        let mut profile = profile("csv", vec![TransformStep::ForwardSlash]);
+        profile.join = Join {
+            style: JoinStyle::Csv,
+            header: "# {count} files\n".to_string(),
+            footer: "\n".to_string(),
+        };
+        let paths = vec![r"C:\a,b.txt".to_string(), r"C:\c.txt".to_string()];
+        let result = transform_files(&profile, &paths).unwrap();
+
+        assert_eq!(result.output, "# 2 files\n\"C:/a,b.txt\",C:/c.txt\n");
+    }
+
+    #[test]
+    fn count_template_excludes_skipped_lines() {
+        let mut profile = profile("lines", vec![TransformStep::ForwardSlash]);
+        profile.on_mixed = OnMixed::SkipLine;
+        profile.join = Join {
+            style: JoinStyle::Lines,
+            header: "# {count} paths\n".to_string(),
+            footer: String::new(),
+        };
+        let result = transform_text(&profile, "see:\nC:\\a.txt\nD:\\b.txt")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(result.output, "# 2 paths\nsee:\nC:/a.txt\nD:/b.txt");
+        assert_eq!(result.skipped, 1);
+    }
+
+    #[test]
+    fn joined_styles_drop_skipped_lines() {
+        let mut profile = profile("shell", vec![TransformStep::ForwardSlash]);
+        profile.on_mixed = OnMixed::SkipLine;
+        profile.join.style = JoinStyle::ShellArgs;
+        let result = transform_text(&profile, "see:\nC:\\a\nD:\\b c")
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.output, "C:/a 'D:/b c'");
+        assert_eq!(result.path_count, 2);
+        assert_eq!(result.skipped, 1);
+
+        profile.join.style = JoinStyle::JsonArray;
+        let result = transform_text(&profile, "see:\nC:\\a").unwrap().unwrap();
+        assert_eq!(result.output, r#"["C:/a"]"#);
+    }
+
+    #[test]
+    fn joins_with_custom_separator() {
+        let mut profile = profile("sep", vec![TransformStep::ForwardSlash]);
+        profile.join.style = JoinStyle::Separator(";".to_string());
+        let paths = vec![r"C:\a".to_string(), r"D:\b".to_string()];
+        let result = transform_files(&profile, &paths).unwrap();
+
+        assert_eq!(result.output, "C:/a;D:/b");
+    }
 }
+
+#[cfg(test)]
+mod corpus;
+
+#[cfg(test)]
+mod properties;
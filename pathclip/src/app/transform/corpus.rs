@@ -0,0 +1,117 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use super::{transform_files, transform_text};
+use crate::app::settings::Settings;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Case {
+    profile: toml::Table,
+    text: Option<String>,
+    files: Option<Vec<String>>,
+    output: Option<String>,
+    error: Option<String>,
+    #[serde(default)]
+    not_paths: bool,
+    path_count: Option<usize>,
+    skipped: Option<usize>,
+}
+
+fn case_files() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/transform");
+    let mut files = fs::read_dir(&dir)
+        .unwrap_or_else(|err| panic!("failed to read {}: {err}", dir.display()))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .collect::<Vec<_>>();
+    files.sort();
+    files
+}
+
+fn run_case(path: &Path) -> Result<(), String> {
+    let source = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let case: Case = toml::from_str(&source).map_err(|err| format!("invalid case: {err}"))?;
+
+    let mut profiles = toml::Table::new();
+    profiles.insert("case".to_string(), case.profile.into());
+    let mut document = toml::Table::new();
+    document.insert("auto_profile".to_string(), "case".into());
+    document.insert("profiles".to_string(), profiles.into());
+    let settings = Settings::parse(&document.to_string()).map_err(|err| format!("{err:#}"))?;
+    let profile = settings.auto_profile().expect("case profile must exist");
+
+    let result = match (&case.text, &case.files) {
+        (Some(text), None) => transform_text(profile, text),
+        (None, Some(files)) => transform_files(profile, files).map(Some),
+        _ => return Err("case must set exactly one of `text` or `files`".to_string()),
+    };
+
+    match (result, &case.output, &case.error) {
+        (Ok(Some(result)), Some(output), None) => {
+            if &result.output != output {
+                return Err(format!(
+                    "expected output {output:?}, got {:?}",
+                    result.output
+                ));
+            }
+            if let Some(path_count) = case.path_count
+                && result.path_count != path_count
+            {
+                return Err(format!(
+                    "expected {path_count} paths, got {}",
+                    result.path_count
+                ));
+            }
+            if let Some(skipped) = case.skipped
+                && result.skipped != skipped
+            {
+                return Err(format!(
+                    "expected {skipped} skipped, got {}",
+                    result.skipped
+                ));
+            }
+            Ok(())
+        }
+        (Ok(None), None, None) if case.not_paths => Ok(()),
+        (Err(err), None, Some(expected)) => {
+            let message = format!("{err:#}");
+            if message.contains(expected.as_str()) {
+                Ok(())
+            } else {
+                Err(format!(
+                    "expected error containing {expected:?}, got {message:?}"
+                ))
+            }
+        }
+        (result, _, _) => Err(format!(
+            "case expectation does not match result {:?}",
+            result.map_err(|err| format!("{err:#}"))
+        )),
+    }
+}
+
+#[test]
+fn golden_cases() {
+    let files = case_files();
+    assert!(!files.is_empty(), "no transform cases found");
+
+    let failures = files
+        .iter()
+        .filter_map(|path| {
+            run_case(path).err().map(|err| {
+                let name = path.file_name().unwrap().to_string_lossy();
+                format!("{name}: {err}")
+            })
+        })
+        .collect::<Vec<_>>();
+    assert!(
+        failures.is_empty(),
+        "{} of {} cases failed:\n{}",
+        failures.len(),
+        files.len(),
+        failures.join("\n")
+    );
+}
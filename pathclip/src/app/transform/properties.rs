@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+use proptest::prelude::*;
+
+use super::{
+    Join, OnMixed, Profile, TransformStep, normalize_extended_path, path_parts, split_lines,
+    transform_files, transform_text,
+};
+
+const PREFIXES: [&str; 8] = [
+    "",
+    r"\\?\",
+    r"\\?\UNC\",
+    r"\\",
+    "//",
+    "C:",
+    r"\\wsl$\",
+    r"\\wsl.localhost\",
+];
+
+fn profile(step: TransformStep, on_mixed: OnMixed) -> Profile {
+    Profile {
+        name: "prop".to_string(),
+        hotkey: None,
+        keep_files: false,
+        paste: false,
+        restore_delay: Duration::ZERO,
+        on_mixed,
+        join: Join::default(),
+        steps: vec![step],
+    }
+}
+
+fn builtin_profiles() -> Vec<Profile> {
+    [OnMixed::Abort, OnMixed::SkipLine]
+        .into_iter()
+        .flat_map(|on_mixed| {
+            [
+                TransformStep::ForwardSlash,
+                TransformStep::Wsl,
+                TransformStep::FileUri,
+            ]
+            .map(|step| profile(step, on_mixed))
+        })
+        .collect()
+}
+
+fn path_like() -> impl Strategy<Value = String> {
+    (prop::sample::select(&PREFIXES[..]), "(?s).{0,16}")
+        .prop_map(|(prefix, rest)| format!("{prefix}{rest}"))
+}
+
+fn drive_path() -> impl Strategy<Value = String> {
+    (
+        prop::char::range('A', 'Z'),
+        prop::collection::vec("[a-zA-Z0-9 _.é中文-]{1,8}", 1..4),
+    )
+        .prop_map(|(drive, segments)| format!(r"{drive}:\{}", segments.join(r"\")))
+}
+
+fn line_ending() -> impl Strategy<Value = &'static str> {
+    prop::sample::select(&["\r\n", "\n", "\r"][..])
+}
+
+proptest! {
+    #[test]
+    fn arbitrary_text_never_panics(input in prop_oneof![any::<String>(), path_like()]) {
+        for profile in builtin_profiles() {
+            let _ = transform_text(&profile, &input);
+            let _ = transform_files(&profile, std::slice::from_ref(&input));
+        }
+        let _ = normalize_extended_path(&input);
+        let _ = path_parts(&input);
+    }
+
+    #[test]
+    fn split_lines_round_trips(input in "(?s).{0,64}") {
+        let joined = split_lines(&input)
+            .iter()
+            .map(|line| format!("{}{}", line.content, line.ending))
+            .collect::<String>();
+        prop_assert_eq!(joined, input);
+    }
+
+    #[test]
+    fn line_endings_are_preserved(
+        lines in prop::collection::vec((drive_path(), line_ending()), 1..6),
+        trailing in any::<bool>(),
+    ) {
+        let mut input = String::new();
+        for (index, (path, ending)) in lines.iter().enumerate() {
+            input.push_str(path);
+            if trailing || index + 1 < lines.len() {
+                input.push_str(ending);
+            }
+        }
+        let slash = profile(TransformStep::ForwardSlash, OnMixed::Abort);
+
+        let result = transform_text(&slash, &input).unwrap().unwrap();
+
+        let endings = |text: &str| {
+            split_lines(text)
+                .iter()
+                .map(|line| line.ending.to_string())
+                .collect::<Vec<_>>()
+        };
+        prop_assert_eq!(endings(&result.output), endings(&input));
+        prop_assert_eq!(result.path_count, lines.len());
+    }
+
+    #[test]
+    fn forward_slash_is_idempotent(path in drive_path()) {
+        let slash = profile(TransformStep::ForwardSlash, OnMixed::Abort);
+
+        let once = transform_text(&slash, &path).unwrap().unwrap().output;
+        let twice = transform_text(&slash, &once).unwrap().unwrap().output;
+
+        prop_assert_eq!(once, twice);
+    }
+
+    #[test]
+    fn skip_line_counts_every_file(paths in prop::collection::vec(path_like(), 1..6)) {
+        let wsl = profile(TransformStep::Wsl, OnMixed::SkipLine);
+
+        if let Ok(result) = transform_files(&wsl, &paths) {
+            prop_assert_eq!(result.path_count + result.skipped, paths.len());
+        }
+    }
+}
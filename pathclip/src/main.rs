@@ -1,7 +1,7 @@
-#[cfg(target_os = "windows")]
+#[cfg(any(target_os = "windows", target_os = "linux"))]
 mod app;
 
-#[cfg(target_os = "windows")]
+#[cfg(any(target_os = "windows", target_os = "linux"))]
 #[tokio::main(flavor = "multi_thread")]
 async fn main() {
     if let Err(err) = app::run().await {
@@ -10,7 +10,7 @@ async fn main() {
     }
 }
 
-#[cfg(not(target_os = "windows"))]
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
 fn main() {
-    eprintln!("pathclip only supports Windows.");
+    eprintln!("pathclip only supports Windows and Linux.");
 }